@@ -0,0 +1,75 @@
+//! Parses a large DACL repeatedly, and reports the time and heap allocations per parse.
+//!
+//! Run with `cargo bench`, and compare with `cargo bench --features smallvec`.
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+use winstructs::security::Acl;
+
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+const ACE_COUNT: u16 = 1500;
+const ITERATIONS: u32 = 200;
+
+/// An ACL with `ACE_COUNT` ACCESS_ALLOWED entries for S-1-5-21-1-2-3-<index>.
+fn build_acl() -> Vec<u8> {
+    let ace_size: u16 = 4 + 4 + 8 + 5 * 4;
+    let acl_size = 8 + ace_size * ACE_COUNT;
+
+    let mut buffer = vec![0x02, 0x00];
+    buffer.extend_from_slice(&acl_size.to_le_bytes());
+    buffer.extend_from_slice(&ACE_COUNT.to_le_bytes());
+    buffer.extend_from_slice(&[0x00, 0x00]);
+
+    for index in 0..u32::from(ACE_COUNT) {
+        buffer.extend_from_slice(&[0x00, 0x00]);
+        buffer.extend_from_slice(&ace_size.to_le_bytes());
+        buffer.extend_from_slice(&0x001F_01FF_u32.to_le_bytes());
+        buffer.extend_from_slice(&[0x01, 0x05, 0x00, 0x00, 0x00, 0x00, 0x00, 0x05]);
+
+        for sub_authority in &[21, 1, 2, 3, 1000 + index] {
+            buffer.extend_from_slice(&u32::to_le_bytes(*sub_authority));
+        }
+    }
+
+    buffer
+}
+
+fn main() {
+    let buffer = build_acl();
+
+    let allocations_before = ALLOCATIONS.load(Ordering::Relaxed);
+    let start = Instant::now();
+
+    for _ in 0..ITERATIONS {
+        let acl = Acl::from_buffer(&buffer).expect("ACL should parse");
+        assert_eq!(acl.entries.len(), usize::from(ACE_COUNT));
+    }
+
+    let elapsed = start.elapsed();
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - allocations_before;
+
+    println!(
+        "parse_acl ({} ACEs): {:?}/iter, {} allocations/iter",
+        ACE_COUNT,
+        elapsed / ITERATIONS,
+        allocations / ITERATIONS as usize
+    );
+}
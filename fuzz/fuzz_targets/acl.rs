@@ -0,0 +1,9 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use winstructs::security::{Ace, Acl};
+
+fuzz_target!(|data: &[u8]| {
+    // Malformed input must result in an error, never a panic.
+    let _ = Acl::from_buffer(data);
+    let _ = Ace::from_buffer(data);
+});
@@ -0,0 +1,8 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use winstructs::security::SecurityDescriptor;
+
+fuzz_target!(|data: &[u8]| {
+    // Malformed input must result in an error, never a panic.
+    let _ = SecurityDescriptor::from_buffer(data);
+});
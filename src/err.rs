@@ -14,4 +14,143 @@ pub enum Error {
     },
     #[error("Unknown AceType: {}", ace_type)]
     UnknownAceType { ace_type: u8 },
+    #[error("Unsupported {} revision: {}", structure, found)]
+    UnsupportedRevision { structure: &'static str, found: u8 },
+    #[error(
+        "ACE of type {} is not allowed in an ACL of revision {}",
+        ace_type,
+        revision
+    )]
+    AceIncompatibleWithAclRevision { ace_type: u8, revision: u8 },
+    #[error(
+        "{} offset {} is outside of the {} byte security descriptor",
+        field,
+        offset,
+        len
+    )]
+    OffsetOutOfBounds {
+        field: &'static str,
+        offset: u32,
+        len: u64,
+    },
+    #[error("A SID can have at most 15 sub authorities, got {}", count)]
+    TooManySubAuthorities { count: usize },
+    #[error("MFT entry {} does not fit in 48 bits", entry)]
+    MftEntryOutOfRange { entry: u64 },
+    #[error("SID authority {} does not fit in 48 bits", authority)]
+    AuthorityOutOfRange { authority: u64 },
+    #[error("Truncated header, expected {} bytes but got {}", expected, got)]
+    TruncatedHeader { expected: usize, got: usize },
+    #[error("Timestamp {} is outside of the representable date range", value)]
+    TimestampOutOfRange { value: u64 },
+    #[error("{} can't be represented as a FILETIME", datetime)]
+    DateTimeOutOfRange {
+        datetime: chrono::DateTime<chrono::Utc>,
+    },
+    #[error("Truncated GUID, expected 16 bytes but got {}", got)]
+    TruncatedGuid { got: usize },
+    #[error("ACL of {} bytes can't hold {} ACEs", size, count)]
+    AceCountExceedsAclSize { count: u16, size: u16 },
+    #[error(
+        "ACL declares a size of {} bytes, but its ACEs take up {}",
+        declared,
+        actual
+    )]
+    AclSizeMismatch { declared: u16, actual: usize },
+    #[error("Invalid MS-DOS time: {:#06x}", raw)]
+    InvalidDosTime { raw: u16 },
+    #[error("{} can't be represented as an MS-DOS date and time", datetime)]
+    DosDateTimeOutOfRange { datetime: chrono::NaiveDateTime },
+    #[error("{} does not exist in the local timezone", datetime)]
+    NonexistentLocalTime { datetime: chrono::NaiveDateTime },
+    #[error(
+        "SID is prefixed with a length of {} bytes, but takes up {}",
+        declared,
+        actual
+    )]
+    SidLengthMismatch { declared: u32, actual: usize },
+    #[error("Invalid data run header: {:#04x}", header)]
+    InvalidDataRunHeader { header: u8 },
+    #[error("Data run offset {} overflows the LCN {}", offset, lcn)]
+    DataRunLcnOverflow { lcn: i64, offset: i64 },
+    #[error(
+        "Output buffer is too small, expected {} bytes but got {}",
+        expected,
+        got
+    )]
+    BufferTooSmall { expected: usize, got: usize },
+    #[error(
+        "ACE declares a size of {} bytes, which is smaller than its header",
+        size
+    )]
+    InvalidAceSize { size: u16 },
+    #[error(
+        "SID declares {} sub authorities, but only {} could be read",
+        expected,
+        read
+    )]
+    TruncatedSid { read: usize, expected: u8 },
+    #[error("ACL declares {} ACEs, but {} were parsed", declared, parsed)]
+    AceCountMismatch { declared: u16, parsed: usize },
+    #[error("Invalid SID pattern: {}", value)]
+    InvalidSidPattern { value: String },
+    #[error("Invalid SID string: {}", value)]
+    InvalidSidString { value: String },
+    #[error("Invalid GUID string: {}", value)]
+    InvalidGuidString { value: String },
+    #[error("Invalid hex string: {}", value)]
+    InvalidHexString { value: String },
+    #[error("Failed to decode UTF-16 string")]
+    Utf16DecodeError {
+        #[from]
+        source: std::string::FromUtf16Error,
+    },
+}
+
+/// Allows parse errors to be propagated with `?` in functions returning `io::Result`.
+impl From<Error> for std::io::Error {
+    fn from(err: Error) -> Self {
+        match err {
+            Error::IoError { source } => source,
+            err @ (Error::TruncatedHeader { .. }
+            | Error::TruncatedGuid { .. }
+            | Error::TruncatedSid { .. }) => {
+                std::io::Error::new(std::io::ErrorKind::UnexpectedEof, err)
+            }
+            err => std::io::Error::new(std::io::ErrorKind::InvalidData, err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Error;
+    use std::io;
+
+    #[test]
+    fn test_converts_into_io_error() {
+        let err: io::Error = Error::UnknownAceType { ace_type: 0xFF }.into();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(err.to_string(), "Unknown AceType: 255");
+
+        let err: io::Error = Error::from(io::Error::from(io::ErrorKind::UnexpectedEof)).into();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+
+        let err: io::Error = Error::TruncatedSid {
+            read: 3,
+            expected: 5,
+        }
+        .into();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+
+        let err: io::Error = Error::TruncatedHeader {
+            expected: 20,
+            got: 4,
+        }
+        .into();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+
+        let err: io::Error = Error::TruncatedGuid { got: 8 }.into();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
 }
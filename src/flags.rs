@@ -0,0 +1,97 @@
+//! Alternative serialization for the flag sets in this crate.
+//!
+//! By default, flag sets serialize as their names (`"SE_DACL_PRESENT | SE_SELF_RELATIVE"`).
+//! The helpers here serialize the raw numeric value instead.
+use bitflags::Flags;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Wraps a flag set so it serializes as its raw bits.
+///
+/// # Example
+///
+/// ```
+/// # use winstructs::flags::AsBits;
+/// # use winstructs::security::SdControlFlags;
+/// let flags = SdControlFlags::SE_DACL_PRESENT;
+///
+/// assert_eq!(serde_json::to_string(&flags).unwrap(), "\"SE_DACL_PRESENT\"");
+/// assert_eq!(serde_json::to_string(&AsBits(flags)).unwrap(), "4");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AsBits<F>(pub F);
+
+impl<F> Serialize for AsBits<F>
+where
+    F: Flags,
+    F::Bits: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        as_bits::serialize(&self.0, serializer)
+    }
+}
+
+impl<'de, F> Deserialize<'de> for AsBits<F>
+where
+    F: Flags,
+    F::Bits: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        as_bits::deserialize(deserializer).map(AsBits)
+    }
+}
+
+/// For use with `#[serde(with = "winstructs::flags::as_bits")]` on flag set fields.
+///
+/// Unknown bits are retained when deserializing.
+pub mod as_bits {
+    use bitflags::Flags;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<F, S>(flags: &F, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        F: Flags,
+        F::Bits: Serialize,
+        S: Serializer,
+    {
+        flags.bits().serialize(serializer)
+    }
+
+    pub fn deserialize<'de, F, D>(deserializer: D) -> Result<F, D::Error>
+    where
+        F: Flags,
+        F::Bits: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        Ok(F::from_bits_retain(F::Bits::deserialize(deserializer)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::security::SdControlFlags;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Header {
+        #[serde(with = "crate::flags::as_bits")]
+        control_flags: SdControlFlags,
+    }
+
+    #[test]
+    fn test_serialize_flags_as_bits() {
+        let header = Header {
+            control_flags: SdControlFlags::SE_DACL_PRESENT | SdControlFlags::SE_SELF_RELATIVE,
+        };
+
+        let json = serde_json::to_string(&header).unwrap();
+        assert_eq!(json, r#"{"control_flags":32772}"#);
+
+        assert_eq!(serde_json::from_str::<Header>(&json).unwrap(), header);
+    }
+}
@@ -1,14 +1,21 @@
 //! Utilities for reading GUIDs.
 //! GUIDs identify objects such as interfaces, manager entry-point vectors (EPVs), and class objects.
-use crate::err::Result;
+use crate::err::{Error, Result};
+#[cfg(feature = "rand")]
+use crate::timestamp::WinTimestamp;
+use crate::utils;
 
 use std::fmt::{self, Display};
-use std::io::{Cursor, Read};
+use std::io::{Cursor, Read, Write};
+use std::str::FromStr;
 
-use byteorder::{LittleEndian, ReadBytesExt};
-use serde::ser;
+use byteorder::{BigEndian, ByteOrder, LittleEndian};
+use serde::{de, ser};
+#[cfg(feature = "tokio")]
+use tokio::io::{AsyncRead, AsyncReadExt};
 
-#[derive(PartialOrd, PartialEq, Clone, Debug)]
+#[derive(PartialOrd, PartialEq, Eq, Hash, Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 /// https://docs.microsoft.com/en-us/previous-versions/aa373931(v%3Dvs.80)
 /// # Example
 ///
@@ -35,7 +42,7 @@ pub struct Guid {
 
 impl Guid {
     /// Creates a new GUID directly from it's components.
-    pub fn new(data1: u32, data2: u16, data3: u16, data4: [u8; 8]) -> Guid {
+    pub const fn new(data1: u32, data2: u16, data3: u16, data4: [u8; 8]) -> Guid {
         Guid {
             data1,
             data2,
@@ -44,6 +51,64 @@ impl Guid {
         }
     }
 
+    /// The version of the GUID, kept in the top 4 bits of `data3`: 1 for time based and 4 for random GUIDs.
+    pub fn version(&self) -> u8 {
+        (self.data3 >> 12) as u8
+    }
+
+    /// Generates a random (version 4) GUID.
+    #[cfg(feature = "rand")]
+    pub fn new_v4() -> Guid {
+        let bytes: [u8; 16] = rand::random();
+        let mut data4 = [0; 8];
+        data4.copy_from_slice(&bytes[8..]);
+
+        Guid::new(
+            u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+            u16::from_le_bytes([bytes[4], bytes[5]]),
+            u16::from_le_bytes([bytes[6], bytes[7]]),
+            data4,
+        )
+        .with_version(4)
+    }
+
+    /// Generates a time based (version 1) GUID from the current time and `node`,
+    /// which is usually a MAC address. The clock sequence is random.
+    #[cfg(feature = "rand")]
+    pub fn new_v1(node: [u8; 6]) -> Guid {
+        // Version 1 timestamps count the 100ns intervals since 1582-10-15 rather than 1601-01-01.
+        const GREGORIAN_EPOCH_INTERVALS: u64 = 5_748_192_000_000_000;
+
+        let timestamp = WinTimestamp::now().as_u64() + GREGORIAN_EPOCH_INTERVALS;
+        let clock_sequence: u16 = rand::random();
+        let [clock_sequence_high, clock_sequence_low] = clock_sequence.to_be_bytes();
+
+        Guid::new(
+            timestamp as u32,
+            (timestamp >> 32) as u16,
+            (timestamp >> 48) as u16,
+            [
+                clock_sequence_high,
+                clock_sequence_low,
+                node[0],
+                node[1],
+                node[2],
+                node[3],
+                node[4],
+                node[5],
+            ],
+        )
+        .with_version(1)
+    }
+
+    /// Sets the version bits, and the variant bits to the RFC 4122 variant.
+    #[cfg(feature = "rand")]
+    fn with_version(mut self, version: u8) -> Guid {
+        self.data3 = (self.data3 & 0x0FFF) | (u16::from(version) << 12);
+        self.data4[0] = (self.data4[0] & 0x3F) | 0x80;
+        self
+    }
+
     /// Reads a GUID from a buffer.
     pub fn from_buffer(buffer: &[u8]) -> Result<Guid> {
         Self::from_reader(&mut Cursor::new(buffer))
@@ -56,14 +121,184 @@ impl Guid {
 
     /// Reads a GUID from a `Read` instance.
     pub fn from_reader<T: Read>(stream: &mut T) -> Result<Guid> {
-        let data1 = stream.read_u32::<LittleEndian>()?;
-        let data2 = stream.read_u16::<LittleEndian>()?;
-        let data3 = stream.read_u16::<LittleEndian>()?;
+        Self::from_reader_endian::<T, LittleEndian>(stream)
+    }
+
+    /// Reads a GUID whose first three fields are stored with the byte order `B`.
+    /// The last 8 bytes are always read as is.
+    pub fn from_reader_endian<T: Read, B: ByteOrder>(stream: &mut T) -> Result<Guid> {
+        let mut buffer = [0; 16];
+        let got = utils::read_up_to(stream, &mut buffer)?;
+
+        if got < buffer.len() {
+            return Err(Error::TruncatedGuid { got });
+        }
 
         let mut data4 = [0; 8];
-        stream.read_exact(&mut data4)?;
+        data4.copy_from_slice(&buffer[8..16]);
+
+        Ok(Guid::new(
+            B::read_u32(&buffer[0..4]),
+            B::read_u16(&buffer[4..6]),
+            B::read_u16(&buffer[6..8]),
+            data4,
+        ))
+    }
+
+    /// Writes the GUID in its 16 byte binary form, the inverse of `from_reader`.
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_all(&self.to_bytes())?;
+
+        Ok(())
+    }
+
+    /// The GUID in its 16 byte binary form, with the first three fields little endian.
+    pub fn to_bytes(&self) -> [u8; 16] {
+        let mut bytes = [0; 16];
+        self.write_bytes(&mut bytes);
+
+        bytes
+    }
+
+    /// Writes the 16 byte binary form of the GUID into the start of `out`,
+    /// erroring if it is shorter than that.
+    pub fn to_buffer(&self, out: &mut [u8]) -> Result<()> {
+        match out.get_mut(..16) {
+            Some(out) => {
+                self.write_bytes(out);
+                Ok(())
+            }
+            None => Err(Error::BufferTooSmall {
+                expected: 16,
+                got: out.len(),
+            }),
+        }
+    }
+
+    /// Parses a GUID that may be wrapped in braces, as in registry exports (`{54849625-...}`),
+    /// or prefixed with `urn:uuid:`. Unwrapped GUIDs are accepted as well.
+    pub fn from_braced_str(s: &str) -> Result<Guid> {
+        let invalid = || Error::InvalidGuidString {
+            value: s.to_string(),
+        };
+
+        let unprefixed = match s.get(..URN_PREFIX.len()) {
+            Some(prefix) if prefix.eq_ignore_ascii_case(URN_PREFIX) => &s[URN_PREFIX.len()..],
+            _ => s,
+        };
+
+        let unwrapped = match (unprefixed.strip_prefix('{'), unprefixed.ends_with('}')) {
+            (Some(rest), true) => &rest[..rest.len() - 1],
+            (None, false) => unprefixed,
+            _ => return Err(invalid()),
+        };
 
-        Ok(Guid::new(data1, data2, data3, data4))
+        unwrapped.parse().map_err(|_| invalid())
+    }
+
+    fn write_bytes(&self, out: &mut [u8]) {
+        LittleEndian::write_u32(&mut out[0..4], self.data1);
+        LittleEndian::write_u16(&mut out[4..6], self.data2);
+        LittleEndian::write_u16(&mut out[6..8], self.data3);
+        out[8..16].copy_from_slice(&self.data4);
+    }
+
+    /// Reads a GUID from an `AsyncRead` instance.
+    #[cfg(feature = "tokio")]
+    pub async fn from_async_reader<T: AsyncRead + Unpin>(stream: &mut T) -> Result<Guid> {
+        let mut buffer = [0; 16];
+        stream.read_exact(&mut buffer).await?;
+
+        Self::from_buffer(&buffer)
+    }
+}
+
+impl Guid {
+    /// Parses the hyphenated form in a const context, panicking on malformed input.
+    /// Prefer the `guid!` macro, which turns the panic into a compile error.
+    #[doc(hidden)]
+    pub const fn parse_const(s: &str) -> Guid {
+        const fn hex_digit(c: u8) -> u8 {
+            match c {
+                b'0'..=b'9' => c - b'0',
+                b'a'..=b'f' => c - b'a' + 10,
+                b'A'..=b'F' => c - b'A' + 10,
+                _ => panic!("invalid hex digit in GUID"),
+            }
+        }
+
+        let s = s.as_bytes();
+        if s.len() != 36 {
+            panic!("a GUID must be 36 characters long");
+        }
+
+        let mut bytes = [0_u8; 16];
+        let mut position = 0;
+        let mut i = 0;
+
+        while i < 16 {
+            if matches!(i, 4 | 6 | 8 | 10) {
+                if s[position] != b'-' {
+                    panic!("expected a hyphen in GUID");
+                }
+                position += 1;
+            }
+
+            bytes[i] = hex_digit(s[position]) << 4 | hex_digit(s[position + 1]);
+            position += 2;
+            i += 1;
+        }
+
+        Guid::new(
+            u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+            u16::from_be_bytes([bytes[4], bytes[5]]),
+            u16::from_be_bytes([bytes[6], bytes[7]]),
+            [
+                bytes[8], bytes[9], bytes[10], bytes[11], bytes[12], bytes[13], bytes[14],
+                bytes[15],
+            ],
+        )
+    }
+
+    /// Renders the uppercase hyphenated form into a stack buffer.
+    fn to_hyphenated_bytes(&self) -> [u8; 36] {
+        const HEX: &[u8; 16] = b"0123456789ABCDEF";
+
+        let mut bytes = [0; 16];
+        bytes[0..4].copy_from_slice(&self.data1.to_be_bytes());
+        bytes[4..6].copy_from_slice(&self.data2.to_be_bytes());
+        bytes[6..8].copy_from_slice(&self.data3.to_be_bytes());
+        bytes[8..16].copy_from_slice(&self.data4);
+
+        let mut out = [b'-'; 36];
+        let mut position = 0;
+
+        for (i, byte) in bytes.iter().enumerate() {
+            if matches!(i, 4 | 6 | 8 | 10) {
+                position += 1;
+            }
+
+            out[position] = HEX[usize::from(byte >> 4)];
+            out[position + 1] = HEX[usize::from(byte & 0xF)];
+            position += 2;
+        }
+
+        out
+    }
+}
+
+/// Compares against the hyphenated form, case insensitively and without allocating.
+impl PartialEq<str> for Guid {
+    fn eq(&self, other: &str) -> bool {
+        other
+            .as_bytes()
+            .eq_ignore_ascii_case(&self.to_hyphenated_bytes())
+    }
+}
+
+impl PartialEq<&str> for Guid {
+    fn eq(&self, other: &&str) -> bool {
+        *self == **other
     }
 }
 
@@ -87,6 +322,66 @@ impl Display for Guid {
     }
 }
 
+const URN_PREFIX: &str = "urn:uuid:";
+
+/// Parses the hyphenated form produced by `Display`, case insensitively.
+impl FromStr for Guid {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Guid> {
+        let invalid = || Error::InvalidGuidString {
+            value: s.to_string(),
+        };
+
+        let groups: Vec<&str> = s.split('-').collect();
+        let group_lengths = groups.iter().map(|group| group.len());
+
+        if !group_lengths.eq([8, 4, 4, 4, 12].iter().copied()) {
+            return Err(invalid());
+        }
+
+        let bytes = utils::from_hex_string(&groups.concat()).map_err(|_| invalid())?;
+
+        let mut data4 = [0; 8];
+        data4.copy_from_slice(&bytes[8..16]);
+
+        Ok(Guid::new(
+            BigEndian::read_u32(&bytes[0..4]),
+            BigEndian::read_u16(&bytes[4..6]),
+            BigEndian::read_u16(&bytes[6..8]),
+            data4,
+        ))
+    }
+}
+
+/// Parses a hyphenated GUID literal at compile time.
+///
+/// # Example
+///
+/// ```
+/// # use winstructs::guid;
+/// # use winstructs::guid::Guid;
+/// const SCHEMA_GUID: Guid = guid!("54849625-5478-4994-A5BA-3E3B0328C30D");
+///
+/// assert_eq!(SCHEMA_GUID.to_string(), "54849625-5478-4994-A5BA-3E3B0328C30D");
+/// ```
+///
+/// A malformed literal fails to compile:
+///
+/// ```compile_fail
+/// # use winstructs::guid;
+/// let _ = guid!("54849625-5478-4994-A5BA-3E3B0328C30G");
+/// ```
+#[macro_export]
+macro_rules! guid {
+    ($s:literal) => {{
+        const GUID: $crate::guid::Guid = $crate::guid::Guid::parse_const($s);
+        GUID
+    }};
+}
+
+impl_json_schema_as_string!(Guid, Some("uuid"));
+
 /// For GUIDs, a string representation is preferable to a struct for serialization.
 impl ser::Serialize for Guid {
     fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
@@ -96,3 +391,170 @@ impl ser::Serialize for Guid {
         serializer.serialize_str(&self.to_string())
     }
 }
+
+impl<'de> de::Deserialize<'de> for Guid {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::err::Error;
+    use crate::guid::Guid;
+    use byteorder::{BigEndian, LittleEndian};
+    use std::io::Cursor;
+
+    #[test]
+    fn test_guid_from_reader_endian() {
+        let raw_guid: &[u8] = &[
+            0x25, 0x96, 0x84, 0x54, 0x78, 0x54, 0x94, 0x49, 0xa5, 0xba, 0x3e, 0x3b, 0x3, 0x28,
+            0xc3, 0xd,
+        ];
+
+        let little =
+            Guid::from_reader_endian::<_, LittleEndian>(&mut Cursor::new(raw_guid)).unwrap();
+        assert_eq!(little, Guid::from_buffer(raw_guid).unwrap());
+        assert_eq!(little.to_string(), "54849625-5478-4994-A5BA-3E3B0328C30D");
+
+        let big = Guid::from_reader_endian::<_, BigEndian>(&mut Cursor::new(raw_guid)).unwrap();
+        assert_eq!(big.to_string(), "25968454-7854-9449-A5BA-3E3B0328C30D");
+    }
+
+    #[test]
+    fn test_guid_version() {
+        let guid: Guid = "54849625-5478-4994-A5BA-3E3B0328C30D".parse().unwrap();
+        assert_eq!(guid.version(), 4);
+
+        let guid: Guid = "1131F6AA-9C07-11D1-F79F-00C04FC2DCD2".parse().unwrap();
+        assert_eq!(guid.version(), 1);
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn test_generates_guids() {
+        let guid = Guid::new_v4();
+        assert_eq!(guid.version(), 4);
+        assert_eq!(guid.data4[0] & 0xC0, 0x80);
+        assert_ne!(guid, Guid::new_v4());
+
+        let node = [0x00, 0xC0, 0x4F, 0xC2, 0xDC, 0xD2];
+        let guid = Guid::new_v1(node);
+        assert_eq!(guid.version(), 1);
+        assert_eq!(guid.data4[0] & 0xC0, 0x80);
+        assert_eq!(guid.data4[2..], node);
+        assert!(guid.to_string().ends_with("-00C04FC2DCD2"));
+    }
+
+    #[test]
+    fn test_guid_to_buffer() {
+        let raw_guid: &[u8] = &[
+            0x25, 0x96, 0x84, 0x54, 0x78, 0x54, 0x94, 0x49, 0xa5, 0xba, 0x3e, 0x3b, 0x3, 0x28,
+            0xc3, 0xd,
+        ];
+
+        let guid = Guid::from_buffer(raw_guid).unwrap();
+        assert_eq!(guid.to_bytes(), raw_guid);
+
+        let mut out = [0xFF; 20];
+        guid.to_buffer(&mut out[2..]).unwrap();
+        assert_eq!(&out[..2], &[0xFF, 0xFF]);
+        assert_eq!(&out[2..18], raw_guid);
+        assert_eq!(&out[18..], &[0xFF, 0xFF]);
+
+        assert!(matches!(
+            guid.to_buffer(&mut out[8..]),
+            Err(Error::BufferTooSmall {
+                expected: 16,
+                got: 12
+            })
+        ));
+    }
+
+    #[test]
+    fn test_guid_eq_str() {
+        let raw_guid: &[u8] = &[
+            0x25, 0x96, 0x84, 0x54, 0x78, 0x54, 0x94, 0x49, 0xa5, 0xba, 0x3e, 0x3b, 0x3, 0x28,
+            0xc3, 0xd,
+        ];
+
+        let guid = Guid::from_buffer(raw_guid).unwrap();
+
+        assert!(guid == "54849625-5478-4994-A5BA-3E3B0328C30D");
+        assert!(guid == *"54849625-5478-4994-a5ba-3e3b0328c30d");
+        assert!(guid != "54849625-5478-4994-A5BA-3E3B0328C30E");
+        assert!(guid != "548496255478-4994-A5BA-3E3B0328C30D-");
+        assert!(guid != "");
+    }
+
+    #[test]
+    fn test_truncated_guid() {
+        let raw_guid: &[u8] = &[0x25, 0x96, 0x84, 0x54, 0x78, 0x54, 0x94, 0x49, 0xa5, 0xba];
+
+        assert!(matches!(
+            Guid::from_buffer(raw_guid),
+            Err(Error::TruncatedGuid { got: 10 })
+        ));
+    }
+
+    #[test]
+    fn test_guid_macro() {
+        let raw_guid: &[u8] = &[
+            0x25, 0x96, 0x84, 0x54, 0x78, 0x54, 0x94, 0x49, 0xa5, 0xba, 0x3e, 0x3b, 0x3, 0x28,
+            0xc3, 0xd,
+        ];
+
+        assert_eq!(
+            crate::guid!("54849625-5478-4994-a5ba-3E3B0328C30D"),
+            Guid::from_buffer(raw_guid).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_guid_from_str() {
+        let raw_guid: &[u8] = &[
+            0x25, 0x96, 0x84, 0x54, 0x78, 0x54, 0x94, 0x49, 0xa5, 0xba, 0x3e, 0x3b, 0x3, 0x28,
+            0xc3, 0xd,
+        ];
+
+        let guid: Guid = "54849625-5478-4994-a5ba-3E3B0328C30D".parse().unwrap();
+
+        assert_eq!(guid, Guid::from_buffer(raw_guid).unwrap());
+        assert!("54849625-5478-4994-A5BA3E3B-0328C30D"
+            .parse::<Guid>()
+            .is_err());
+        assert!("54849625-5478-4994-A5BA-3E3B0328C30G"
+            .parse::<Guid>()
+            .is_err());
+    }
+
+    #[test]
+    fn test_guid_from_braced_str() {
+        let expected: Guid = "54849625-5478-4994-A5BA-3E3B0328C30D".parse().unwrap();
+
+        for s in [
+            "{54849625-5478-4994-A5BA-3E3B0328C30D}",
+            "urn:uuid:54849625-5478-4994-a5ba-3e3b0328c30d",
+            "54849625-5478-4994-A5BA-3E3B0328C30D",
+        ] {
+            assert_eq!(Guid::from_braced_str(s).unwrap(), expected);
+        }
+
+        for s in [
+            "{54849625-5478-4994-A5BA-3E3B0328C30D",
+            "54849625-5478-4994-A5BA-3E3B0328C30D}",
+            "{}",
+            "urn:uuid:",
+        ] {
+            assert!(matches!(
+                Guid::from_braced_str(s),
+                Err(Error::InvalidGuidString { value }) if value == s
+            ));
+        }
+    }
+}
@@ -0,0 +1,54 @@
+//! Hex rendering and parsing of raw byte blobs, matching the format used when serializing unparsed structures.
+use crate::err::Result;
+
+/// Renders the bytes as a contiguous uppercase hex string.
+///
+/// # Example
+///
+/// ```
+/// # use winstructs::hex::to_hex_string;
+/// assert_eq!(to_hex_string(&[0xDE, 0xAD]), "DEAD");
+/// ```
+pub fn to_hex_string(bytes: &[u8]) -> String {
+    crate::utils::to_hex_string(bytes)
+}
+
+/// Parses a string of hex digit pairs, in either case, back into bytes.
+/// The inverse of `to_hex_string`, erroring on odd-length or non-hex input.
+///
+/// # Example
+///
+/// ```
+/// # use winstructs::hex::from_hex_string;
+/// assert_eq!(from_hex_string("deAD").unwrap(), vec![0xDE, 0xAD]);
+/// assert!(from_hex_string("DEA").is_err());
+/// ```
+pub fn from_hex_string(s: &str) -> Result<Vec<u8>> {
+    crate::utils::from_hex_string(s)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{from_hex_string, to_hex_string};
+    use crate::err::Error;
+
+    #[test]
+    fn test_hex_round_trip() {
+        let bytes: Vec<u8> = (0..=255).collect();
+
+        let hex = to_hex_string(&bytes);
+        assert_eq!(hex.len(), 512);
+        assert!(hex.starts_with("000102"));
+        assert!(hex.ends_with("FDFEFF"));
+
+        assert_eq!(from_hex_string(&hex).unwrap(), bytes);
+        assert_eq!(from_hex_string("").unwrap(), Vec::<u8>::new());
+
+        for invalid in ["A", "0G", "+1"] {
+            assert!(matches!(
+                from_hex_string(invalid),
+                Err(Error::InvalidHexString { value }) if value == invalid
+            ));
+        }
+    }
+}
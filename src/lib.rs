@@ -9,6 +9,25 @@
 //! `from_buffer` is also provided as convenience, but it generally just builds a cursor and uses
 //! either `from_reader` or `from_stream` internally.
 //!
+//! # Features
+//! - `tokio` - adds `from_async_reader`/`from_async_stream` variants for some structs,
+//!   which read from `tokio::io::AsyncRead` (and `AsyncSeek`) instances.
+//! - `arbitrary` - implements `arbitrary::Arbitrary` for the parsed structures, for use with fuzzers.
+//! - `schemars` - implements `schemars::JsonSchema` for the serializable structures,
+//!   describing their JSON representation.
+//! - `smallvec` - stores SID sub authorities inline, avoiding a heap allocation for most SIDs.
+//! - `serde-camelcase` - serializes the fields of the security descriptor structures in `camelCase`
+//!   (`ownerSid`, `controlFlags`, ...) instead of `snake_case`.
+//! - `serde-access-masks` - serializes the access rights of ACEs as both the raw mask and the names
+//!   of its rights (`{"raw": 2032127, "rights": ["SA_RIGHT_DELETE", ...]}`), decoded as file rights.
+//! - `rand` - adds `Guid::new_v4` and `Guid::new_v1`, for generating GUIDs.
+//! - `clock` - adds `WinTimestamp::to_local_datetime`, for timestamps stored in local time.
+//!   This needs the timezone of the system.
+//!
+//! # Fuzzing
+//! Parsing arbitrary bytes must never panic - malformed input should always result in an `Err`.
+//! The `fuzz` directory contains `cargo fuzz` targets which check this property.
+//!
 #![deny(unused_must_use)]
 #![deny(unsafe_code)]
 // Don't allow dbg! prints in release.
@@ -16,23 +35,181 @@
 
 use std::io::{self, Read, Seek, SeekFrom};
 
-#[macro_use]
-extern crate num_derive;
-
 #[macro_use]
 pub(crate) mod macros;
 pub(crate) mod utils;
 
 pub mod err;
+pub mod flags;
 pub mod guid;
+pub mod hex;
 pub mod ntfs;
 pub mod security;
 pub mod timestamp;
 
+pub use crate::utils::{
+    read_utf16_le, read_utf16_le_null_terminated, CountingReader, VerifyingReader,
+};
+
 pub trait ReadSeek: Read + Seek {
     fn tell(&mut self) -> io::Result<u64> {
-        self.seek(SeekFrom::Current(0))
+        self.stream_position()
+    }
+
+    /// Wraps the stream in a reader which will not read more than `len` bytes past the current position.
+    fn take_bounded(&mut self, len: u64) -> Bounded<&mut Self>
+    where
+        Self: Sized,
+    {
+        Bounded::new(self, len)
     }
 }
 
 impl<T: Read + Seek> ReadSeek for T {}
+
+/// Convenience methods for readers, available on every `Read`.
+pub trait ReadExt: Read {
+    /// Reads exactly `N` bytes into an array.
+    /// (Not named `read_array`, since that would clash with an unstable method of `Read`.)
+    fn read_byte_array<const N: usize>(&mut self) -> io::Result<[u8; N]> {
+        let mut buffer = [0; N];
+        self.read_exact(&mut buffer)?;
+
+        Ok(buffer)
+    }
+}
+
+impl<R: Read + ?Sized> ReadExt for R {}
+
+/// A reader adapter which refuses to read past a declared length.
+///
+/// Reads past the limit will return EOF, which allows parsing a structure against its declared size
+/// without consuming data belonging to neighboring structures.
+/// Positions reported by `Seek` are relative to where the adapter was created.
+///
+/// # Example
+///
+/// ```
+/// # use winstructs::Bounded;
+/// # use std::io::{Cursor, Read};
+/// let mut reader = Bounded::new(Cursor::new(&[1, 2, 3, 4]), 2);
+/// let mut buf = vec![];
+///
+/// reader.read_to_end(&mut buf).unwrap();
+///
+/// assert_eq!(buf, vec![1, 2]);
+/// ```
+#[derive(Debug)]
+pub struct Bounded<R> {
+    inner: R,
+    position: u64,
+    limit: u64,
+}
+
+impl<R> Bounded<R> {
+    pub fn new(inner: R, limit: u64) -> Self {
+        Bounded {
+            inner,
+            position: 0,
+            limit,
+        }
+    }
+
+    /// Number of bytes which can still be read before hitting the limit.
+    pub fn remaining(&self) -> u64 {
+        self.limit.saturating_sub(self.position)
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read> Read for Bounded<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let max = std::cmp::min(buf.len() as u64, self.remaining()) as usize;
+
+        if max == 0 {
+            return Ok(0);
+        }
+
+        let n = self.inner.read(&mut buf[..max])?;
+        self.position += n as u64;
+
+        Ok(n)
+    }
+}
+
+impl<R: Seek> Seek for Bounded<R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let target = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::Current(delta) => self.position.checked_add_signed(delta),
+            SeekFrom::End(delta) => self.limit.checked_add_signed(delta),
+        }
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            )
+        })?;
+
+        let delta = target.wrapping_sub(self.position) as i64;
+        self.inner.seek(SeekFrom::Current(delta))?;
+        self.position = target;
+
+        Ok(target)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ReadExt, ReadSeek};
+    use std::io::{Cursor, Read, Seek, SeekFrom};
+
+    #[test]
+    fn test_read_byte_array() {
+        let mut cursor = Cursor::new(&[0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07]);
+
+        assert_eq!(
+            cursor.read_byte_array::<6>().unwrap(),
+            [0x01, 0x02, 0x03, 0x04, 0x05, 0x06]
+        );
+
+        let err = cursor.read_byte_array::<2>().unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn test_bounded_reader_stops_at_limit() {
+        let buffer: &[u8] = &[0x01, 0x02, 0x03, 0x04, 0x05, 0x06];
+        let mut cursor = Cursor::new(buffer);
+        cursor.seek(SeekFrom::Start(1)).unwrap();
+
+        let mut bounded = cursor.take_bounded(3);
+
+        let mut data = [0; 3];
+        bounded.read_exact(&mut data).unwrap();
+        assert_eq!(data, [0x02, 0x03, 0x04]);
+        assert_eq!(bounded.tell().unwrap(), 3);
+
+        let mut one = [0; 1];
+        let err = bounded.read_exact(&mut one).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+
+        assert_eq!(cursor.position(), 4);
+    }
+
+    #[test]
+    fn test_bounded_reader_seek() {
+        let buffer: &[u8] = &[0x01, 0x02, 0x03, 0x04, 0x05, 0x06];
+        let mut cursor = Cursor::new(buffer);
+
+        let mut bounded = cursor.take_bounded(4);
+        bounded.seek(SeekFrom::End(-1)).unwrap();
+
+        let mut data = vec![];
+        bounded.read_to_end(&mut data).unwrap();
+        assert_eq!(data, vec![0x04]);
+    }
+}
@@ -1,3 +1,6 @@
+/// The token an empty flag set is serialized as, so it can't be mistaken for a missing value.
+pub(crate) const EMPTY_FLAGS: &str = "NONE";
+
 macro_rules! impl_serialize_for_bitflags {
     ($flags: ident) => {
         impl serde::ser::Serialize for $flags {
@@ -5,7 +8,74 @@ macro_rules! impl_serialize_for_bitflags {
             where
                 S: serde::ser::Serializer,
             {
-                serializer.serialize_str(&format!("{:?}", &self))
+                if self.is_empty() {
+                    return serializer.serialize_str($crate::macros::EMPTY_FLAGS);
+                }
+
+                let mut names = String::new();
+                bitflags::parser::to_writer(self, &mut names)
+                    .expect("Writing to a String cannot fail");
+
+                serializer.serialize_str(&names)
+            }
+        }
+    };
+}
+
+/// Parses the `FLAG_A | FLAG_B` form emitted by `impl_serialize_for_bitflags`.
+macro_rules! impl_deserialize_for_bitflags {
+    ($flags: ident) => {
+        impl<'de> serde::de::Deserialize<'de> for $flags {
+            fn deserialize<D>(deserializer: D) -> ::std::result::Result<Self, D::Error>
+            where
+                D: serde::de::Deserializer<'de>,
+            {
+                let names = String::deserialize(deserializer)?;
+
+                if names == $crate::macros::EMPTY_FLAGS {
+                    return Ok($flags::empty());
+                }
+
+                bitflags::parser::from_str(&names).map_err(serde::de::Error::custom)
+            }
+        }
+    };
+}
+
+macro_rules! impl_arbitrary_for_bitflags {
+    ($flags: ident) => {
+        #[cfg(feature = "arbitrary")]
+        impl<'a> arbitrary::Arbitrary<'a> for $flags {
+            fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+                Ok($flags::from_bits_truncate(u.arbitrary()?))
+            }
+        }
+    };
+}
+
+/// Implements `JsonSchema` for types which serialize as a string, optionally with a `format`.
+macro_rules! impl_json_schema_as_string {
+    ($ty: ident) => {
+        impl_json_schema_as_string!($ty, None);
+    };
+    ($ty: ident, $format: expr) => {
+        #[cfg(feature = "schemars")]
+        impl schemars::JsonSchema for $ty {
+            fn is_referenceable() -> bool {
+                false
+            }
+
+            fn schema_name() -> String {
+                stringify!($ty).to_owned()
+            }
+
+            fn json_schema(_: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+                schemars::schema::SchemaObject {
+                    instance_type: Some(schemars::schema::InstanceType::String.into()),
+                    format: $format.map(|format: &str| format.to_owned()),
+                    ..Default::default()
+                }
+                .into()
             }
         }
     };
@@ -0,0 +1,181 @@
+use crate::err::{Error, Result};
+use byteorder::ReadBytesExt;
+use serde::Serialize;
+
+use std::io::{Cursor, Read};
+
+/// A single run of clusters of a non-resident attribute.
+#[derive(Serialize, Debug, Hash, Eq, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct DataRun {
+    /// The absolute cluster number the run starts at, or `None` for a sparse run.
+    pub lcn: Option<i64>,
+    /// The number of clusters in the run.
+    pub length: u64,
+}
+
+impl DataRun {
+    pub fn is_sparse(&self) -> bool {
+        self.lcn.is_none()
+    }
+}
+
+/// The run list of a non-resident attribute, which maps its virtual clusters to clusters on the volume.
+/// https://flatcap.github.io/linux-ntfs/ntfs/concepts/data_runs.html
+/// # Example
+///
+/// ```
+/// # use winstructs::ntfs::data_runs::{DataRun, DataRuns};
+/// let raw_runs: &[u8] = &[0x21, 0x10, 0x00, 0x01, 0x00];
+///
+/// let runs = DataRuns::from_buffer(raw_runs).unwrap();
+///
+/// assert_eq!(runs.0, vec![DataRun { lcn: Some(0x100), length: 0x10 }]);
+/// ```
+#[derive(Serialize, Debug, Hash, Eq, PartialEq, Clone)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct DataRuns(pub Vec<DataRun>);
+
+impl DataRuns {
+    pub fn from_buffer(buffer: &[u8]) -> Result<Self> {
+        Self::from_reader(&mut Cursor::new(buffer))
+    }
+
+    /// Reads runs up to (and including) the 0x00 terminator.
+    pub fn from_reader<R: Read>(reader: &mut R) -> Result<DataRuns> {
+        let mut runs = Vec::new();
+        // The offset of each run is relative to the start of the previous non-sparse run.
+        let mut lcn: i64 = 0;
+
+        loop {
+            let header = reader.read_u8()?;
+
+            if header == 0 {
+                break;
+            }
+
+            // The low nibble is the size of the length field, the high nibble that of the offset field.
+            let length_size = usize::from(header & 0x0F);
+            let offset_size = usize::from(header >> 4);
+
+            if length_size == 0 || length_size > 8 || offset_size > 8 {
+                return Err(Error::InvalidDataRunHeader { header });
+            }
+
+            let mut length_bytes = [0; 8];
+            reader.read_exact(&mut length_bytes[..length_size])?;
+            let length = u64::from_le_bytes(length_bytes);
+
+            // A run without an offset is sparse, and doesn't move the current position.
+            if offset_size == 0 {
+                runs.push(DataRun { lcn: None, length });
+                continue;
+            }
+
+            let mut offset_bytes = [0; 8];
+            reader.read_exact(&mut offset_bytes[..offset_size])?;
+
+            // The offset is signed, so sign extend it from its most significant stored byte.
+            if offset_bytes[offset_size - 1] & 0x80 != 0 {
+                for byte in offset_bytes[offset_size..].iter_mut() {
+                    *byte = 0xFF;
+                }
+            }
+
+            let offset = i64::from_le_bytes(offset_bytes);
+            lcn = lcn
+                .checked_add(offset)
+                .ok_or(Error::DataRunLcnOverflow { lcn, offset })?;
+
+            runs.push(DataRun {
+                lcn: Some(lcn),
+                length,
+            });
+        }
+
+        Ok(DataRuns(runs))
+    }
+
+    /// The total number of clusters covered by the runs, including sparse ones.
+    pub fn total_clusters(&self) -> u64 {
+        self.0.iter().map(|run| run.length).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DataRun, DataRuns};
+    use crate::err::Error;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_parses_data_runs() {
+        let raw_runs: &[u8] = &[
+            0x21, 0x10, 0x00, 0x01, // 0x10 clusters at 0x100
+            0x01, 0x08, // 8 sparse clusters
+            0x11, 0x20, 0xE0, // 0x20 clusters, 0x20 clusters back
+            0x31, 0x05, 0x00, 0x10, 0x00, // 5 clusters, 0x1000 clusters forward
+            0x00, // terminator
+            0xFF, 0xFF,
+        ];
+
+        let mut reader = Cursor::new(raw_runs);
+        let runs = DataRuns::from_reader(&mut reader).unwrap();
+
+        assert_eq!(
+            runs.0,
+            vec![
+                DataRun {
+                    lcn: Some(0x100),
+                    length: 0x10
+                },
+                DataRun {
+                    lcn: None,
+                    length: 8
+                },
+                DataRun {
+                    lcn: Some(0xE0),
+                    length: 0x20
+                },
+                DataRun {
+                    lcn: Some(0x10E0),
+                    length: 5
+                },
+            ]
+        );
+        assert!(runs.0[1].is_sparse());
+        assert_eq!(runs.total_clusters(), 0x3D);
+
+        // Nothing past the terminator is consumed.
+        assert_eq!(reader.position(), 15);
+    }
+
+    #[test]
+    fn test_rejects_invalid_data_runs() {
+        assert!(matches!(
+            DataRuns::from_buffer(&[0x10, 0x01, 0x00]),
+            Err(Error::InvalidDataRunHeader { header: 0x10 })
+        ));
+        assert!(matches!(
+            DataRuns::from_buffer(&[0x19, 0x01, 0x00]),
+            Err(Error::InvalidDataRunHeader { header: 0x19 })
+        ));
+
+        // Missing terminator.
+        assert!(DataRuns::from_buffer(&[0x11, 0x01, 0x01]).is_err());
+
+        // A run at the largest LCN, followed by one past it.
+        let buffer: &[u8] = &[
+            0x81, 0x01, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x7F, // LCN i64::MAX
+            0x81, 0x01, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // +1
+            0x00,
+        ];
+        assert!(matches!(
+            DataRuns::from_buffer(buffer),
+            Err(Error::DataRunLcnOverflow {
+                lcn: i64::MAX,
+                offset: 1
+            })
+        ));
+    }
+}
@@ -0,0 +1,112 @@
+use crate::err::Result;
+use bitflags::bitflags;
+use byteorder::{LittleEndian, ReadBytesExt};
+
+use std::io::{Cursor, Read};
+
+// File attribute flags, as stored in `$STANDARD_INFORMATION` and `$FILE_NAME`.
+// https://docs.microsoft.com/en-us/windows/win32/fileio/file-attribute-constants
+// https://github.com/libyal/libfsntfs/blob/main/documentation/New%20Technologies%20File%20System%20(NTFS).asciidoc#file-attribute-flags
+bitflags! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+    pub struct FileAttributeFlags: u32 {
+        const FILE_ATTRIBUTE_READONLY               = 0x0000_0001;
+        const FILE_ATTRIBUTE_HIDDEN                 = 0x0000_0002;
+        const FILE_ATTRIBUTE_SYSTEM                 = 0x0000_0004;
+        const FILE_ATTRIBUTE_DIRECTORY              = 0x0000_0010;
+        const FILE_ATTRIBUTE_ARCHIVE                = 0x0000_0020;
+        const FILE_ATTRIBUTE_DEVICE                 = 0x0000_0040;
+        const FILE_ATTRIBUTE_NORMAL                 = 0x0000_0080;
+        const FILE_ATTRIBUTE_TEMPORARY              = 0x0000_0100;
+        const FILE_ATTRIBUTE_SPARSE_FILE            = 0x0000_0200;
+        const FILE_ATTRIBUTE_REPARSE_POINT          = 0x0000_0400;
+        const FILE_ATTRIBUTE_COMPRESSED             = 0x0000_0800;
+        const FILE_ATTRIBUTE_OFFLINE                = 0x0000_1000;
+        const FILE_ATTRIBUTE_NOT_CONTENT_INDEXED    = 0x0000_2000;
+        const FILE_ATTRIBUTE_ENCRYPTED              = 0x0000_4000;
+        const FILE_ATTRIBUTE_INTEGRITY_STREAM       = 0x0000_8000;
+        const FILE_ATTRIBUTE_VIRTUAL                = 0x0001_0000;
+        const FILE_ATTRIBUTE_NO_SCRUB_DATA          = 0x0002_0000;
+        const FILE_ATTRIBUTE_RECALL_ON_OPEN         = 0x0004_0000;
+        const FILE_ATTRIBUTE_PINNED                 = 0x0008_0000;
+        const FILE_ATTRIBUTE_UNPINNED               = 0x0010_0000;
+        const FILE_ATTRIBUTE_RECALL_ON_DATA_ACCESS  = 0x0040_0000;
+        // NTFS specific, set on directories (and views) which have an index.
+        const FILE_ATTRIBUTE_IS_DIRECTORY           = 0x1000_0000;
+        const FILE_ATTRIBUTE_INDEX_VIEW             = 0x2000_0000;
+    }
+}
+
+impl_serialize_for_bitflags! {FileAttributeFlags}
+impl_deserialize_for_bitflags! {FileAttributeFlags}
+impl_arbitrary_for_bitflags! {FileAttributeFlags}
+impl_json_schema_as_string! {FileAttributeFlags}
+
+impl FileAttributeFlags {
+    pub fn from_buffer(buffer: &[u8]) -> Result<Self> {
+        Self::from_reader(&mut Cursor::new(buffer))
+    }
+
+    /// Bits which are not known to this crate are retained as is.
+    pub fn from_reader<R: Read>(reader: &mut R) -> Result<FileAttributeFlags> {
+        Ok(FileAttributeFlags::from_bits_retain(
+            reader.read_u32::<LittleEndian>()?,
+        ))
+    }
+
+    /// `$STANDARD_INFORMATION` usually marks directories only with the NTFS specific index flag,
+    /// so both are checked.
+    pub fn is_directory(&self) -> bool {
+        self.intersects(
+            FileAttributeFlags::FILE_ATTRIBUTE_DIRECTORY
+                | FileAttributeFlags::FILE_ATTRIBUTE_IS_DIRECTORY,
+        )
+    }
+
+    pub fn is_reparse_point(&self) -> bool {
+        self.contains(FileAttributeFlags::FILE_ATTRIBUTE_REPARSE_POINT)
+    }
+
+    pub fn is_compressed(&self) -> bool {
+        self.contains(FileAttributeFlags::FILE_ATTRIBUTE_COMPRESSED)
+    }
+
+    pub fn is_sparse(&self) -> bool {
+        self.contains(FileAttributeFlags::FILE_ATTRIBUTE_SPARSE_FILE)
+    }
+
+    pub fn is_encrypted(&self) -> bool {
+        self.contains(FileAttributeFlags::FILE_ATTRIBUTE_ENCRYPTED)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FileAttributeFlags;
+
+    #[test]
+    fn test_file_attribute_predicates() {
+        let flags = FileAttributeFlags::from_buffer(&[0x30, 0x00, 0x00, 0x00]).unwrap();
+
+        assert_eq!(
+            flags,
+            FileAttributeFlags::FILE_ATTRIBUTE_DIRECTORY
+                | FileAttributeFlags::FILE_ATTRIBUTE_ARCHIVE
+        );
+        assert!(flags.is_directory());
+        assert!(!flags.is_reparse_point());
+        assert!(!flags.is_compressed());
+
+        assert_eq!(
+            serde_json::to_value(flags).unwrap(),
+            "FILE_ATTRIBUTE_DIRECTORY | FILE_ATTRIBUTE_ARCHIVE"
+        );
+
+        // A directory as seen from its $STANDARD_INFORMATION, which is also a compressed junction.
+        let flags = FileAttributeFlags::from_buffer(&[0x00, 0x0C, 0x00, 0x10]).unwrap();
+
+        assert!(flags.is_directory());
+        assert!(flags.is_reparse_point());
+        assert!(flags.is_compressed());
+    }
+}
@@ -1,4 +1,4 @@
-use crate::err::Result;
+use crate::err::{Error, Result};
 use byteorder::{ByteOrder, LittleEndian, ReadBytesExt};
 use serde::Serialize;
 
@@ -6,6 +6,7 @@ use serde::Serialize;
 /// https://msdn.microsoft.com/en-us/library/bb470211(v=vs.85).aspx
 /// https://jmharkness.wordpress.com/2011/01/27/mft-file-reference-number/
 #[derive(Serialize, Debug, Hash, Eq, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct MftReference {
     pub entry: u64,
     pub sequence: u16,
@@ -18,6 +19,26 @@ impl MftReference {
         MftReference { entry, sequence }
     }
 
+    /// Like `new`, but errors if the entry doesn't fit in the 6 bytes it occupies on disk.
+    pub fn checked_new(entry: u64, sequence: u16) -> Result<Self> {
+        if entry >= 1 << 48 {
+            return Err(Error::MftEntryOutOfRange { entry });
+        }
+
+        Ok(MftReference { entry, sequence })
+    }
+
+    /// The null reference (zero entry and sequence), which is used by unlinked records.
+    pub fn is_null(&self) -> bool {
+        self.entry == 0 && self.sequence == 0
+    }
+
+    /// Whether the reference still points at the same file, given the `actual` sequence number of the
+    /// record it refers to. A mismatch means the record was since reused, so the reference is stale.
+    pub fn matches_sequence(&self, actual: u16) -> bool {
+        self.sequence == actual
+    }
+
     pub fn from_reader<R: Read>(reader: &mut R) -> Result<Self> {
         Ok(Self::from(reader.read_u64::<LittleEndian>()?))
     }
@@ -40,6 +61,15 @@ impl From<u64> for MftReference {
     }
 }
 
+/// Packs the reference back into its on-disk form: the entry in the low 6 bytes and the sequence
+/// in the high 2. This is the inverse of `From<u64>`, for references whose entry fits in 48 bits
+/// (see `checked_new`).
+impl From<MftReference> for u64 {
+    fn from(reference: MftReference) -> Self {
+        (reference.entry & 0xFFFF_FFFF_FFFF) | (u64::from(reference.sequence) << 48)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::MftReference;
@@ -52,5 +82,38 @@ mod tests {
         let mft_reference = MftReference::from_reader(&mut Cursor::new(raw_reference)).unwrap();
         assert_eq!(mft_reference.entry, 115);
         assert_eq!(mft_reference.sequence, 37224);
+        assert!(!mft_reference.is_null());
+    }
+
+    #[test]
+    fn test_matches_sequence() {
+        let mft_reference = MftReference::new(115, 37224);
+
+        assert!(mft_reference.matches_sequence(37224));
+        assert!(!mft_reference.matches_sequence(37225));
+    }
+
+    #[test]
+    fn test_checked_new() {
+        assert!(MftReference::checked_new(1 << 48, 1).is_err());
+        assert_eq!(
+            MftReference::checked_new((1 << 48) - 1, 1).unwrap(),
+            MftReference::new((1 << 48) - 1, 1)
+        );
+
+        assert!(MftReference::from(0).is_null());
+        assert!(MftReference::checked_new(0, 0).unwrap().is_null());
+        assert!(!MftReference::new(0, 1).is_null());
+    }
+
+    #[test]
+    fn test_packed_round_trip() {
+        let packed = 0x9168_0000_0000_0073;
+
+        let mft_reference = MftReference::from(packed);
+        assert_eq!(mft_reference, MftReference::new(115, 37224));
+        assert_eq!(u64::from(mft_reference), packed);
+
+        assert_eq!(u64::from(MftReference::new(5, 5)), 0x0005_0000_0000_0005);
     }
 }
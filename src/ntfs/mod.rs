@@ -1,3 +1,12 @@
 //! Utilities for reading structures found in the NTFS file system, and used in other formats.
 
+pub mod data_runs;
+pub mod file_attributes;
 pub mod mft_reference;
+pub mod object_id;
+pub mod reparse_tag;
+pub mod volume_serial_number;
+
+pub use self::file_attributes::FileAttributeFlags;
+pub use self::object_id::ObjectId;
+pub use self::volume_serial_number::VolumeSerialNumber;
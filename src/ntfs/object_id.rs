@@ -0,0 +1,114 @@
+use crate::err::{Error, Result};
+use crate::guid::Guid;
+use serde::Serialize;
+
+use std::io::{Cursor, Read};
+
+/// The `$OBJECT_ID` attribute, which identifies a file for link tracking.
+/// It holds between one and four GUIDs, depending on its length: only the object id is mandatory.
+/// https://flatcap.github.io/linux-ntfs/ntfs/attributes/object_id.html
+/// # Example
+///
+/// ```
+/// # use winstructs::ntfs::ObjectId;
+/// let raw_object_id: &[u8] = &[
+///     0x25, 0x96, 0x84, 0x54, 0x78, 0x54, 0x94, 0x49, 0xa5, 0xba, 0x3e, 0x3b, 0x03, 0x28, 0xc3, 0x0d,
+/// ];
+///
+/// let object_id = ObjectId::from_buffer(raw_object_id).unwrap();
+///
+/// assert_eq!(object_id.object_id.to_string(), "54849625-5478-4994-A5BA-3E3B0328C30D");
+/// assert!(object_id.birth_volume_id.is_none());
+/// ```
+#[derive(Serialize, Debug, Hash, Eq, PartialEq, Clone)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct ObjectId {
+    pub object_id: Guid,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub birth_volume_id: Option<Guid>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub birth_object_id: Option<Guid>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub domain_id: Option<Guid>,
+}
+
+impl ObjectId {
+    pub fn from_buffer(buffer: &[u8]) -> Result<Self> {
+        Self::from_reader(&mut Cursor::new(buffer))
+    }
+
+    /// Reads the attribute until the reader ends, so it should be limited to the length of the attribute.
+    /// A GUID cut short by the end of the reader is an error.
+    pub fn from_reader<R: Read>(reader: &mut R) -> Result<ObjectId> {
+        let object_id = Guid::from_reader(reader)?;
+        let birth_volume_id = read_optional_guid(reader)?;
+
+        // Each GUID is only present if the previous one is.
+        let birth_object_id = match birth_volume_id {
+            Some(_) => read_optional_guid(reader)?,
+            None => None,
+        };
+        let domain_id = match birth_object_id {
+            Some(_) => read_optional_guid(reader)?,
+            None => None,
+        };
+
+        Ok(ObjectId {
+            object_id,
+            birth_volume_id,
+            birth_object_id,
+            domain_id,
+        })
+    }
+}
+
+fn read_optional_guid<R: Read>(reader: &mut R) -> Result<Option<Guid>> {
+    match Guid::from_reader(reader) {
+        Ok(guid) => Ok(Some(guid)),
+        Err(Error::TruncatedGuid { got: 0 }) => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ObjectId;
+    use crate::err::Error;
+    use crate::guid::Guid;
+
+    #[test]
+    fn test_parses_object_id_only() {
+        let raw_object_id = [0x11; 16];
+
+        let object_id = ObjectId::from_buffer(&raw_object_id).unwrap();
+
+        assert_eq!(object_id.object_id, Guid::from_buffer(&[0x11; 16]).unwrap());
+        assert!(object_id.birth_volume_id.is_none());
+        assert!(object_id.birth_object_id.is_none());
+        assert!(object_id.domain_id.is_none());
+
+        let json = serde_json::to_value(&object_id).unwrap();
+        assert!(json.get("birth_volume_id").is_none());
+    }
+
+    #[test]
+    fn test_parses_full_object_id() {
+        let mut raw_object_id = Vec::new();
+        for byte in [0x11, 0x22, 0x33, 0x44] {
+            raw_object_id.extend_from_slice(&[byte; 16]);
+        }
+
+        let object_id = ObjectId::from_buffer(&raw_object_id).unwrap();
+
+        let guid = |byte| Some(Guid::from_buffer(&[byte; 16]).unwrap());
+        assert_eq!(Some(object_id.object_id), guid(0x11));
+        assert_eq!(object_id.birth_volume_id, guid(0x22));
+        assert_eq!(object_id.birth_object_id, guid(0x33));
+        assert_eq!(object_id.domain_id, guid(0x44));
+
+        assert!(matches!(
+            ObjectId::from_buffer(&raw_object_id[..40]),
+            Err(Error::TruncatedGuid { got: 8 })
+        ));
+    }
+}
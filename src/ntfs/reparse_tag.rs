@@ -0,0 +1,157 @@
+use crate::err::Result;
+use byteorder::{LittleEndian, ReadBytesExt};
+use serde::Serialize;
+
+use std::fmt;
+use std::io::{Cursor, Read};
+
+/// Represents a reparse point tag, as found in the `$REPARSE_POINT` attribute
+/// (and in the `$STANDARD_INFORMATION`/directory index entries of reparse points).
+/// https://docs.microsoft.com/en-us/openspecs/windows_protocols/ms-fscc/c8e77b37-3909-4fe6-a4ea-2b9d423b1ee4
+/// # Example
+///
+/// ```
+/// # use winstructs::ntfs::reparse_tag::ReparseTag;
+/// let raw_tag: &[u8] = &[0x0C, 0x00, 0x00, 0xA0];
+///
+/// let tag = ReparseTag::from_buffer(raw_tag).unwrap();
+///
+/// assert_eq!(tag, ReparseTag::SYMLINK);
+/// assert_eq!(format!("{}", tag), "IO_REPARSE_TAG_SYMLINK");
+/// ```
+#[derive(Serialize, Debug, Hash, Eq, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct ReparseTag(pub u32);
+
+impl ReparseTag {
+    pub const MOUNT_POINT: ReparseTag = ReparseTag(0xA000_0003);
+    pub const HSM: ReparseTag = ReparseTag(0xC000_0004);
+    pub const HSM2: ReparseTag = ReparseTag(0x8000_0006);
+    pub const SIS: ReparseTag = ReparseTag(0x8000_0007);
+    pub const WIM: ReparseTag = ReparseTag(0x8000_0008);
+    pub const CSV: ReparseTag = ReparseTag(0x8000_0009);
+    pub const DFS: ReparseTag = ReparseTag(0x8000_000A);
+    pub const SYMLINK: ReparseTag = ReparseTag(0xA000_000C);
+    pub const DFSR: ReparseTag = ReparseTag(0x8000_0012);
+    pub const DEDUP: ReparseTag = ReparseTag(0x8000_0013);
+    pub const NFS: ReparseTag = ReparseTag(0x8000_0014);
+    pub const FILE_PLACEHOLDER: ReparseTag = ReparseTag(0x8000_0015);
+    pub const WOF: ReparseTag = ReparseTag(0x8000_0017);
+    pub const WCI: ReparseTag = ReparseTag(0x8000_0018);
+    pub const GLOBAL_REPARSE: ReparseTag = ReparseTag(0xA000_0019);
+    pub const CLOUD: ReparseTag = ReparseTag(0x9000_001A);
+    pub const APPEXECLINK: ReparseTag = ReparseTag(0x8000_001B);
+    pub const PROJFS: ReparseTag = ReparseTag(0x9000_001C);
+    pub const LX_SYMLINK: ReparseTag = ReparseTag(0xA000_001D);
+    pub const STORAGE_SYNC: ReparseTag = ReparseTag(0x8000_001E);
+    pub const WCI_TOMBSTONE: ReparseTag = ReparseTag(0xA000_001F);
+    pub const UNHANDLED: ReparseTag = ReparseTag(0x8000_0020);
+    pub const ONEDRIVE: ReparseTag = ReparseTag(0x8000_0021);
+    pub const PROJFS_TOMBSTONE: ReparseTag = ReparseTag(0xA000_0022);
+    pub const AF_UNIX: ReparseTag = ReparseTag(0x8000_0023);
+    pub const LX_FIFO: ReparseTag = ReparseTag(0x8000_0024);
+    pub const LX_CHR: ReparseTag = ReparseTag(0x8000_0025);
+    pub const LX_BLK: ReparseTag = ReparseTag(0x8000_0026);
+    pub const WCI_LINK: ReparseTag = ReparseTag(0xA000_0027);
+
+    pub fn new(tag: u32) -> Self {
+        ReparseTag(tag)
+    }
+
+    pub fn from_buffer(buffer: &[u8]) -> Result<Self> {
+        Self::from_reader(&mut Cursor::new(buffer))
+    }
+
+    pub fn from_reader<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(ReparseTag(reader.read_u32::<LittleEndian>()?))
+    }
+
+    /// Bit 31 is set for tags owned by Microsoft.
+    pub fn is_microsoft(&self) -> bool {
+        self.0 & 0x8000_0000 != 0
+    }
+
+    /// Bit 29 is set for tags that act as a surrogate for another named entity (such as a symlink).
+    pub fn is_name_surrogate(&self) -> bool {
+        self.0 & 0x2000_0000 != 0
+    }
+
+    /// Returns the name of the `IO_REPARSE_TAG_*` constant matching this tag, if it is a known one.
+    pub fn name(&self) -> Option<&'static str> {
+        let name = match *self {
+            ReparseTag::MOUNT_POINT => "IO_REPARSE_TAG_MOUNT_POINT",
+            ReparseTag::HSM => "IO_REPARSE_TAG_HSM",
+            ReparseTag::HSM2 => "IO_REPARSE_TAG_HSM2",
+            ReparseTag::SIS => "IO_REPARSE_TAG_SIS",
+            ReparseTag::WIM => "IO_REPARSE_TAG_WIM",
+            ReparseTag::CSV => "IO_REPARSE_TAG_CSV",
+            ReparseTag::DFS => "IO_REPARSE_TAG_DFS",
+            ReparseTag::SYMLINK => "IO_REPARSE_TAG_SYMLINK",
+            ReparseTag::DFSR => "IO_REPARSE_TAG_DFSR",
+            ReparseTag::DEDUP => "IO_REPARSE_TAG_DEDUP",
+            ReparseTag::NFS => "IO_REPARSE_TAG_NFS",
+            ReparseTag::FILE_PLACEHOLDER => "IO_REPARSE_TAG_FILE_PLACEHOLDER",
+            ReparseTag::WOF => "IO_REPARSE_TAG_WOF",
+            ReparseTag::WCI => "IO_REPARSE_TAG_WCI",
+            ReparseTag::GLOBAL_REPARSE => "IO_REPARSE_TAG_GLOBAL_REPARSE",
+            ReparseTag::CLOUD => "IO_REPARSE_TAG_CLOUD",
+            ReparseTag::APPEXECLINK => "IO_REPARSE_TAG_APPEXECLINK",
+            ReparseTag::PROJFS => "IO_REPARSE_TAG_PROJFS",
+            ReparseTag::LX_SYMLINK => "IO_REPARSE_TAG_LX_SYMLINK",
+            ReparseTag::STORAGE_SYNC => "IO_REPARSE_TAG_STORAGE_SYNC",
+            ReparseTag::WCI_TOMBSTONE => "IO_REPARSE_TAG_WCI_TOMBSTONE",
+            ReparseTag::UNHANDLED => "IO_REPARSE_TAG_UNHANDLED",
+            ReparseTag::ONEDRIVE => "IO_REPARSE_TAG_ONEDRIVE",
+            ReparseTag::PROJFS_TOMBSTONE => "IO_REPARSE_TAG_PROJFS_TOMBSTONE",
+            ReparseTag::AF_UNIX => "IO_REPARSE_TAG_AF_UNIX",
+            ReparseTag::LX_FIFO => "IO_REPARSE_TAG_LX_FIFO",
+            ReparseTag::LX_CHR => "IO_REPARSE_TAG_LX_CHR",
+            ReparseTag::LX_BLK => "IO_REPARSE_TAG_LX_BLK",
+            ReparseTag::WCI_LINK => "IO_REPARSE_TAG_WCI_LINK",
+            _ => return None,
+        };
+
+        Some(name)
+    }
+}
+
+impl From<u32> for ReparseTag {
+    fn from(tag: u32) -> Self {
+        ReparseTag(tag)
+    }
+}
+
+impl fmt::Display for ReparseTag {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.name() {
+            Some(name) => write!(f, "{}", name),
+            None => write!(f, "0x{:08X}", self.0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ReparseTag;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_reparse_tag_symlink() {
+        let raw_tag = vec![0x0C, 0x00, 0x00, 0xA0];
+
+        let tag = ReparseTag::from_reader(&mut Cursor::new(raw_tag)).unwrap();
+        assert_eq!(tag, ReparseTag::SYMLINK);
+        assert_eq!(tag.name(), Some("IO_REPARSE_TAG_SYMLINK"));
+        assert!(tag.is_microsoft());
+        assert!(tag.is_name_surrogate());
+    }
+
+    #[test]
+    fn test_reparse_tag_unknown() {
+        let tag = ReparseTag::new(0x0000_1234);
+
+        assert_eq!(tag.name(), None);
+        assert!(!tag.is_microsoft());
+        assert_eq!(format!("{}", tag), "0x00001234");
+    }
+}
@@ -0,0 +1,57 @@
+use crate::err::Result;
+use byteorder::{LittleEndian, ReadBytesExt};
+use serde::Serialize;
+
+use std::fmt;
+use std::io::{Cursor, Read};
+
+/// The 64 bit serial number of an NTFS volume, as found in the boot sector.
+///
+/// It is displayed the way `vol` and `dir` show it: only the low 32 bits are used,
+/// as two hyphenated groups of 4 hexadecimal digits.
+/// # Example
+///
+/// ```
+/// # use winstructs::ntfs::VolumeSerialNumber;
+/// let raw_serial: &[u8] = &[0xD4, 0xC3, 0xB2, 0xA1, 0x78, 0x56, 0x34, 0x12];
+///
+/// let serial = VolumeSerialNumber::from_buffer(raw_serial).unwrap();
+///
+/// assert_eq!(serial.to_string(), "A1B2-C3D4");
+/// ```
+#[derive(Serialize, Debug, Hash, Eq, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct VolumeSerialNumber(pub u64);
+
+impl VolumeSerialNumber {
+    pub fn from_buffer(buffer: &[u8]) -> Result<Self> {
+        Self::from_reader(&mut Cursor::new(buffer))
+    }
+
+    pub fn from_reader<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(VolumeSerialNumber(reader.read_u64::<LittleEndian>()?))
+    }
+}
+
+impl fmt::Display for VolumeSerialNumber {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let low = self.0 as u32;
+
+        write!(f, "{:04X}-{:04X}", low >> 16, low & 0xFFFF)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::VolumeSerialNumber;
+
+    #[test]
+    fn test_volume_serial_number_display() {
+        let raw_serial: &[u8] = &[0x0F, 0x00, 0xB0, 0x0A, 0xEF, 0xBE, 0xAD, 0xDE];
+
+        let serial = VolumeSerialNumber::from_buffer(raw_serial).unwrap();
+
+        assert_eq!(serial.0, 0xDEAD_BEEF_0AB0_000F);
+        assert_eq!(serial.to_string(), "0AB0-000F");
+    }
+}
@@ -1,133 +1,476 @@
 //! ACE
 //! https://github.com/libyal/libfwnt/wiki/Security-Descriptor#access-control-entry-ace
+//!
+//! Every ACE starts with a 4 byte header: the type, the flags and the little endian size of the
+//! whole ACE. The body which follows depends on the type, but nearly always ends with a SID:
+//!
+//! - basic ACEs: a 4 byte access mask, then the SID (`AceBasic`).
+//! - object ACEs: a 4 byte access mask, 4 bytes of `AceObjectFlags`, the object type and inherited
+//!   object type GUIDs (each only present if its flag is set), then the SID (`AceObject`).
+//! - callback ACEs: the body of their basic or object form, followed by application data.
+//!
+//! Bodies of types which aren't modeled are kept as `RawAce`, and can be decoded with
+//! `AceBasic::from_reader`, `AceObject::from_reader` and `parse_sid_at_end`.
 use crate::err::{Error, Result};
 use crate::guid::Guid;
+use crate::security::ad_rights;
 use crate::security::sid::Sid;
 use crate::utils;
-use bitflags::bitflags;
-use byteorder::{LittleEndian, ReadBytesExt};
-use serde::{ser, Serialize};
-
-use num_traits::FromPrimitive;
+use crate::Bounded;
+use bitflags::{bitflags, Flags};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use serde::{de, ser, Deserialize, Serialize};
 
+use std::convert::TryFrom;
 use std::fmt;
-use std::io::{Cursor, Read};
+use std::hash::{Hash, Hasher};
+use std::io::{self, Cursor, Read, Write};
 
-#[derive(Serialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde-camelcase", serde(rename_all = "camelCase"))]
 pub struct Ace {
     pub ace_type: AceType,
     pub ace_flags: AceFlags,
-    #[serde(skip_serializing)]
+    #[serde(skip_serializing, default)]
     pub size: u16,
     pub data: AceData,
+    /// Bytes covered by `size` which weren't part of the parsed body, such as alignment padding.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub trailing: Option<RawAce>,
+}
+
+/// Like serialization, equality ignores `size`, which only describes the layout.
+impl PartialEq for Ace {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for Ace {}
+
+impl Hash for Ace {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key().hash(state)
+    }
 }
 
 impl Ace {
+    pub fn from_buffer(buffer: &[u8]) -> Result<Self> {
+        Self::from_reader(&mut Cursor::new(buffer))
+    }
+
     pub fn from_reader<R: Read>(reader: &mut R) -> Result<Ace> {
+        Self::read(reader, false)
+    }
+
+    fn key(&self) -> (&AceType, &AceFlags, &AceData, &Option<RawAce>) {
+        (&self.ace_type, &self.ace_flags, &self.data, &self.trailing)
+    }
+
+    /// A key for sorting ACEs into a stable order for display or diffing: by the numeric type,
+    /// then the SID as a string (the server SID for compound ACEs), then the access rights.
+    /// ACEs with an unparsed body sort before the others of their type.
+    ///
+    /// This is not the canonical order Windows expects of a DACL, see `Acl::canonicalize` for that.
+    ///
+    /// Since the key formats the SID, prefer `sort_by_cached_key`, which computes it once per ACE.
+    ///
+    /// ```
+    /// # use winstructs::security::Ace;
+    /// # fn sort(entries: &mut Vec<Ace>) {
+    /// entries.sort_by_cached_key(Ace::sort_key);
+    /// # }
+    /// ```
+    pub fn sort_key(&self) -> (u8, Option<String>, Option<u32>) {
+        let (sid, access_rights) = match &self.data {
+            AceData::Basic(basic) => (Some(&basic.sid), Some(basic.access_rights)),
+            AceData::Object(object) => (Some(&object.sid), Some(object.access_rights)),
+            AceData::Compound(compound) => {
+                (Some(&compound.server_sid), Some(compound.access_rights))
+            }
+            AceData::Unhandled(_) => (None, None),
+        };
+
+        (
+            self.ace_type.as_u8(),
+            sid.map(Sid::to_string),
+            access_rights,
+        )
+    }
+
+    /// When `tolerate_unknown_type` is set, ACEs of an unknown type are kept as `AceType::Unknown`
+    /// with their body unparsed, instead of failing the whole parse.
+    pub(crate) fn read<R: Read>(reader: &mut R, tolerate_unknown_type: bool) -> Result<Ace> {
         let ace_type_byte = reader.read_u8()?;
-        let ace_type = AceType::from_u8(ace_type_byte).ok_or_else(|| Error::UnknownAceType {
-            ace_type: ace_type_byte,
-        })?;
+        let ace_type = match AceType::from_u8(ace_type_byte) {
+            Some(ace_type) => ace_type,
+            None if tolerate_unknown_type => AceType::Unknown(ace_type_byte),
+            None => {
+                return Err(Error::UnknownAceType {
+                    ace_type: ace_type_byte,
+                })
+            }
+        };
 
         let ace_flags = AceFlags::from_bits_truncate(reader.read_u8()?);
         let size = reader.read_u16::<LittleEndian>()?;
 
-        // Create data buffer
-        let mut data_buffer = vec![0; (size - 4) as usize];
-        reader.read_exact(&mut data_buffer)?;
+        // The size includes the 4 byte header.
+        let data_size = size.checked_sub(4).ok_or(Error::InvalidAceSize { size })?;
+
+        // The body is parsed directly from the reader, but never past the declared size.
+        let mut body = Bounded::new(reader, u64::from(data_size));
 
         let data = if ace_type.is_basic() {
-            AceData::Basic(AceBasic::from_reader(&mut Cursor::new(data_buffer))?)
+            let mut basic = AceBasic::from_reader(&mut body)?;
+
+            // Like callback object ACEs, these store more data after the SID.
+            if ace_type.is_callback() || ace_type == AceType::SystemResourceAttribute {
+                basic.application_data = read_remaining(&mut body)?;
+            }
+
+            AceData::Basic(basic)
         } else if ace_type.is_object() {
-            AceData::Object(AceObject::from_reader(&mut Cursor::new(data_buffer))?)
+            let mut object = AceObject::from_reader(&mut body)?;
+
+            // Callback object ACEs store their conditional expression after the SID.
+            if ace_type.is_callback() {
+                object.application_data = read_remaining(&mut body)?;
+            }
+
+            AceData::Object(object)
+        } else if ace_type.is_compound() {
+            AceData::Compound(AceCompound::from_reader(&mut body)?)
         } else {
+            let mut data_buffer = vec![0; data_size as usize];
+            body.read_exact(&mut data_buffer)?;
+
             AceData::Unhandled(RawAce(data_buffer))
         };
 
+        // Keep whatever is left of the body, which also positions the reader at the next ACE.
+        let trailing = read_remaining(&mut body)?;
+
         Ok(Ace {
             ace_type,
             ace_flags,
             size,
             data,
+            trailing,
         })
     }
+
+    /// Writes the ACE in its binary form, the inverse of `from_reader`.
+    /// The size in the header is computed from the contents rather than taken from `size`.
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> Result<()> {
+        let size = u16::try_from(self.byte_len()).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "ACE is too large to be written",
+            )
+        })?;
+
+        writer.write_u8(self.ace_type.as_u8())?;
+        writer.write_u8(self.ace_flags.bits())?;
+        writer.write_u16::<LittleEndian>(size)?;
+
+        match &self.data {
+            AceData::Basic(basic) => basic.write_to(writer)?,
+            AceData::Object(object) => object.write_to(writer)?,
+            AceData::Compound(compound) => compound.write_to(writer)?,
+            AceData::Unhandled(raw) => writer.write_all(&raw.0)?,
+        }
+
+        if let Some(trailing) = &self.trailing {
+            writer.write_all(&trailing.0)?;
+        }
+
+        Ok(())
+    }
+
+    /// The size of the ACE in bytes, including the 4 byte header and any trailing bytes.
+    pub fn byte_len(&self) -> usize {
+        let body = match &self.data {
+            AceData::Basic(basic) => {
+                let application_data = basic
+                    .application_data
+                    .as_ref()
+                    .map_or(0, |application_data| application_data.0.len());
+
+                4 + basic.sid.byte_len() + application_data
+            }
+            AceData::Object(object) => {
+                let guids =
+                    object.object_type.iter().count() + object.inherited_type.iter().count();
+                let application_data = object
+                    .application_data
+                    .as_ref()
+                    .map_or(0, |application_data| application_data.0.len());
+
+                8 + 16 * guids + object.sid.byte_len() + application_data
+            }
+            AceData::Compound(compound) => {
+                8 + compound.server_sid.byte_len() + compound.client_sid.byte_len()
+            }
+            AceData::Unhandled(raw) => raw.0.len(),
+        };
+        let trailing = self
+            .trailing
+            .as_ref()
+            .map_or(0, |trailing| trailing.0.len());
+
+        4 + body + trailing
+    }
+}
+
+/// Parses the SID which ends most ACE bodies, found `offset` bytes into `body` after the fixed size
+/// fields of the type. Returns it along with any bytes following it, such as application data.
+///
+/// # Example
+///
+/// ```
+/// # use winstructs::security::parse_sid_at_end;
+/// let body: &[u8] = &[
+///     0xFF, 0x01, 0x1F, 0x00, // access mask
+///     0x01, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x05, 0x12, 0x00, 0x00, 0x00, // S-1-5-18
+///     0xDE, 0xAD, // application data
+/// ];
+///
+/// let (sid, rest) = parse_sid_at_end(body, 4).unwrap();
+///
+/// assert_eq!(sid.to_string(), "S-1-5-18");
+/// assert_eq!(rest, &[0xDE, 0xAD]);
+/// ```
+pub fn parse_sid_at_end(body: &[u8], offset: usize) -> Result<(Sid, &[u8])> {
+    let sid_buffer = body.get(offset..).ok_or(Error::BufferTooSmall {
+        expected: offset,
+        got: body.len(),
+    })?;
+
+    let (sid, len) = Sid::from_buffer_with_len(sid_buffer)?;
+
+    Ok((sid, &sid_buffer[len..]))
+}
+
+fn read_remaining<R: Read>(body: &mut Bounded<R>) -> Result<Option<RawAce>> {
+    match body.remaining() {
+        0 => Ok(None),
+        remaining => {
+            let mut buffer = vec![0; remaining as usize];
+            body.read_exact(&mut buffer)?;
+
+            Ok(Some(RawAce(buffer)))
+        }
+    }
 }
 
-#[derive(FromPrimitive, ToPrimitive, Serialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
-#[repr(u8)]
 pub enum AceType {
-    AccessAllowed = 0x00,
-    AccessDenied = 0x01,
-    SystemAudit = 0x02,
-    SystemAlarm = 0x03,
-    AccessAllowedCompound = 0x04,
-    AccessAllowedObject = 0x05,
-    AccessDeniedObject = 0x06,
-    SystemAuditObject = 0x07,
-    SystemAlarmObject = 0x08,
-    AccessAllowedCallback = 0x09,
-    AccessDeniedCallback = 0x0a,
-    AccessAllowedCallbackObject = 0x0b,
-    AccessDeniedCallbackObject = 0x0c,
-    SystemAuditCallback = 0x0d,
-    SystemAlarmCallback = 0x0e,
-    SystemAuditCallbackObject = 0x0f,
-    SystemAlarmCallbackObject = 0x10,
-    SystemMandatoryLabel = 0x11,
-    SystemResourceAttribute = 0x12,
-    SystemScopedPolicyID = 0x13,
-    SystemProcessTrustLabel = 0x14,
-    SystemAccessFilter = 0x15,
+    AccessAllowed,
+    AccessDenied,
+    SystemAudit,
+    SystemAlarm,
+    AccessAllowedCompound,
+    AccessAllowedObject,
+    AccessDeniedObject,
+    SystemAuditObject,
+    SystemAlarmObject,
+    AccessAllowedCallback,
+    AccessDeniedCallback,
+    AccessAllowedCallbackObject,
+    AccessDeniedCallbackObject,
+    SystemAuditCallback,
+    SystemAlarmCallback,
+    SystemAuditCallbackObject,
+    SystemAlarmCallbackObject,
+    SystemMandatoryLabel,
+    SystemResourceAttribute,
+    SystemScopedPolicyID,
+    SystemProcessTrustLabel,
+    SystemAccessFilter,
+    /// A type which isn't known to this crate, only produced when parsing leniently.
+    Unknown(u8),
 }
 
 impl AceType {
-    pub fn is_basic(&self) -> bool {
+    /// Maps an on-disk value to a known ACE type.
+    pub fn from_u8(value: u8) -> Option<AceType> {
+        let ace_type = match value {
+            0x00 => AceType::AccessAllowed,
+            0x01 => AceType::AccessDenied,
+            0x02 => AceType::SystemAudit,
+            0x03 => AceType::SystemAlarm,
+            0x04 => AceType::AccessAllowedCompound,
+            0x05 => AceType::AccessAllowedObject,
+            0x06 => AceType::AccessDeniedObject,
+            0x07 => AceType::SystemAuditObject,
+            0x08 => AceType::SystemAlarmObject,
+            0x09 => AceType::AccessAllowedCallback,
+            0x0a => AceType::AccessDeniedCallback,
+            0x0b => AceType::AccessAllowedCallbackObject,
+            0x0c => AceType::AccessDeniedCallbackObject,
+            0x0d => AceType::SystemAuditCallback,
+            0x0e => AceType::SystemAlarmCallback,
+            0x0f => AceType::SystemAuditCallbackObject,
+            0x10 => AceType::SystemAlarmCallbackObject,
+            0x11 => AceType::SystemMandatoryLabel,
+            0x12 => AceType::SystemResourceAttribute,
+            0x13 => AceType::SystemScopedPolicyID,
+            0x14 => AceType::SystemProcessTrustLabel,
+            0x15 => AceType::SystemAccessFilter,
+            _ => return None,
+        };
+
+        Some(ace_type)
+    }
+
+    /// The on-disk value of the ACE type.
+    pub fn as_u8(&self) -> u8 {
         match self {
-            AceType::AccessAllowed
-            | AceType::AccessDenied
-            | AceType::SystemAudit
-            | AceType::SystemAlarm
-            | AceType::AccessAllowedCallback
-            | AceType::AccessDeniedCallback
-            | AceType::SystemAuditCallback
-            | AceType::SystemAlarmCallback
-            | AceType::SystemMandatoryLabel
-            | AceType::SystemResourceAttribute
-            | AceType::SystemScopedPolicyID
-            | AceType::SystemProcessTrustLabel
-            | AceType::SystemAccessFilter => true,
-            _ => false,
+            AceType::AccessAllowed => 0x00,
+            AceType::AccessDenied => 0x01,
+            AceType::SystemAudit => 0x02,
+            AceType::SystemAlarm => 0x03,
+            AceType::AccessAllowedCompound => 0x04,
+            AceType::AccessAllowedObject => 0x05,
+            AceType::AccessDeniedObject => 0x06,
+            AceType::SystemAuditObject => 0x07,
+            AceType::SystemAlarmObject => 0x08,
+            AceType::AccessAllowedCallback => 0x09,
+            AceType::AccessDeniedCallback => 0x0a,
+            AceType::AccessAllowedCallbackObject => 0x0b,
+            AceType::AccessDeniedCallbackObject => 0x0c,
+            AceType::SystemAuditCallback => 0x0d,
+            AceType::SystemAlarmCallback => 0x0e,
+            AceType::SystemAuditCallbackObject => 0x0f,
+            AceType::SystemAlarmCallbackObject => 0x10,
+            AceType::SystemMandatoryLabel => 0x11,
+            AceType::SystemResourceAttribute => 0x12,
+            AceType::SystemScopedPolicyID => 0x13,
+            AceType::SystemProcessTrustLabel => 0x14,
+            AceType::SystemAccessFilter => 0x15,
+            AceType::Unknown(value) => *value,
         }
     }
 
+    pub fn is_basic(&self) -> bool {
+        matches!(
+            self,
+            AceType::AccessAllowed
+                | AceType::AccessDenied
+                | AceType::SystemAudit
+                | AceType::SystemAlarm
+                | AceType::AccessAllowedCallback
+                | AceType::AccessDeniedCallback
+                | AceType::SystemAuditCallback
+                | AceType::SystemAlarmCallback
+                | AceType::SystemMandatoryLabel
+                | AceType::SystemResourceAttribute
+                | AceType::SystemScopedPolicyID
+                | AceType::SystemProcessTrustLabel
+                | AceType::SystemAccessFilter
+        )
+    }
+
+    /// Callback ACEs carry application data, usually a conditional expression, after their SID.
+    pub fn is_callback(&self) -> bool {
+        matches!(
+            self,
+            AceType::AccessAllowedCallback
+                | AceType::AccessDeniedCallback
+                | AceType::AccessAllowedCallbackObject
+                | AceType::AccessDeniedCallbackObject
+                | AceType::SystemAuditCallback
+                | AceType::SystemAlarmCallback
+                | AceType::SystemAuditCallbackObject
+                | AceType::SystemAlarmCallbackObject
+        )
+    }
+
+    /// Types which deny access, including their object and callback forms.
+    pub fn is_access_denied(&self) -> bool {
+        matches!(
+            self,
+            AceType::AccessDenied
+                | AceType::AccessDeniedObject
+                | AceType::AccessDeniedCallback
+                | AceType::AccessDeniedCallbackObject
+        )
+    }
+
+    pub fn is_compound(&self) -> bool {
+        matches!(self, AceType::AccessAllowedCompound)
+    }
+
     pub fn is_object(&self) -> bool {
-        match self {
+        matches!(
+            self,
             AceType::AccessAllowedObject
-            | AceType::AccessDeniedObject
-            | AceType::SystemAuditObject
-            | AceType::SystemAlarmObject
-            | AceType::AccessAllowedCallbackObject
-            | AceType::AccessDeniedCallbackObject
-            | AceType::SystemAuditCallbackObject
-            | AceType::SystemAlarmCallbackObject => true,
-            _ => false,
-        }
+                | AceType::AccessDeniedObject
+                | AceType::SystemAuditObject
+                | AceType::SystemAlarmObject
+                | AceType::AccessAllowedCallbackObject
+                | AceType::AccessDeniedCallbackObject
+                | AceType::SystemAuditCallbackObject
+                | AceType::SystemAlarmCallbackObject
+        )
     }
 }
 
-#[derive(Serialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(untagged)]
 pub enum AceData {
     Basic(AceBasic),
     Object(AceObject),
+    Compound(AceCompound),
     Unhandled(RawAce),
 }
 
-#[derive(Serialize, Debug, Clone)]
+/// Which variant an `AceData` is, without its contents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AceDataKind {
+    Basic,
+    Object,
+    Compound,
+    Unhandled,
+}
+
+impl AceData {
+    pub fn kind(&self) -> AceDataKind {
+        match self {
+            AceData::Basic(_) => AceDataKind::Basic,
+            AceData::Object(_) => AceDataKind::Object,
+            AceData::Compound(_) => AceDataKind::Compound,
+            AceData::Unhandled(_) => AceDataKind::Unhandled,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+// Since `AceData` is untagged, this prevents object ACEs from being deserialized as basic ACEs.
+#[serde(deny_unknown_fields)]
+#[cfg_attr(feature = "serde-camelcase", serde(rename_all = "camelCase"))]
 pub struct AceBasic {
+    #[cfg_attr(feature = "serde-access-masks", serde(with = "access_mask"))]
+    #[cfg_attr(
+        all(feature = "serde-access-masks", feature = "schemars"),
+        schemars(with = "access_mask::AccessMask")
+    )]
     pub access_rights: u32,
     pub sid: Sid,
+    /// The data which follows the SID: the conditional expression of callback ACEs,
+    /// or the claim of `SystemResourceAttribute` ACEs.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub application_data: Option<RawAce>,
 }
 
 impl AceBasic {
@@ -135,25 +478,206 @@ impl AceBasic {
         let access_rights = reader.read_u32::<LittleEndian>()?;
         let sid = Sid::from_reader(&mut reader)?;
 
-        Ok(AceBasic { access_rights, sid })
+        Ok(AceBasic {
+            access_rights,
+            sid,
+            application_data: None,
+        })
+    }
+
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_u32::<LittleEndian>(self.access_rights)?;
+        self.sid.write_to(writer)?;
+
+        if let Some(application_data) = &self.application_data {
+            writer.write_all(&application_data.0)?;
+        }
+
+        Ok(())
+    }
+
+    /// For the ACE of a `SystemProcessTrustLabel`, decodes the protection its SID requires.
+    pub fn process_trust_label(&self) -> Option<ProcessTrustLabel> {
+        ProcessTrustLabel::from_sid(&self.sid)
+    }
+
+    /// Renders the access rights with the flag names of `context`, such as
+    /// `SA_RIGHT_READCONTROL | NFA_RIGHT_READBODY`. Bits without a name are shown in hex.
+    pub fn access_rights_description(&self, context: AccessContext) -> String {
+        access_rights_names(self.access_rights, context).join(" | ")
     }
 }
 
-#[derive(Serialize, Debug, Clone)]
+pub(crate) fn access_rights_names(mask: u32, context: AccessContext) -> Vec<String> {
+    let mut names = Vec::new();
+
+    let mut known = push_flag_names(&mut names, StandardAccessFlags::from_bits_truncate(mask));
+    known |= match context {
+        AccessContext::File => {
+            push_flag_names(&mut names, NonFolderAccessFlags::from_bits_truncate(mask))
+        }
+        AccessContext::Directory => {
+            push_flag_names(&mut names, FolderAccessFlags::from_bits_truncate(mask))
+        }
+        AccessContext::Registry => {
+            push_flag_names(&mut names, RegistryAccessFlags::from_bits_truncate(mask))
+        }
+        AccessContext::Standard => 0,
+    };
+
+    let unknown = mask & !known;
+    if unknown != 0 {
+        names.push(format!("{:#x}", unknown));
+    }
+
+    names
+}
+
+/// The protection required by a `SystemProcessTrustLabel` ACE, which is encoded in its
+/// `S-1-19-<type>-<level>` SID.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ProcessTrustLabel {
+    pub trust_type: u32,
+    pub trust_level: u32,
+}
+
+impl ProcessTrustLabel {
+    /// The authority of process trust label SIDs.
+    pub const AUTHORITY: u64 = 19;
+
+    pub const TYPE_NONE: u32 = 0;
+    pub const TYPE_PROTECTED_LIGHT: u32 = 512;
+    pub const TYPE_PROTECTED: u32 = 1024;
+
+    pub const LEVEL_NONE: u32 = 0;
+    pub const LEVEL_AUTHENTICODE: u32 = 1024;
+    pub const LEVEL_ANTIMALWARE: u32 = 1536;
+    pub const LEVEL_APP: u32 = 2048;
+    pub const LEVEL_WINDOWS: u32 = 4096;
+    pub const LEVEL_WINTCB: u32 = 8192;
+
+    /// Returns `None` if `sid` isn't a process trust label SID.
+    pub fn from_sid(sid: &Sid) -> Option<ProcessTrustLabel> {
+        let sub_authorities = sid.sub_authorities();
+
+        if sid.authority().value() != ProcessTrustLabel::AUTHORITY || sub_authorities.len() != 2 {
+            return None;
+        }
+
+        Some(ProcessTrustLabel {
+            trust_type: sub_authorities[0].value(),
+            trust_level: sub_authorities[1].value(),
+        })
+    }
+
+    pub fn trust_type_name(&self) -> Option<&'static str> {
+        match self.trust_type {
+            ProcessTrustLabel::TYPE_NONE => Some("None"),
+            ProcessTrustLabel::TYPE_PROTECTED_LIGHT => Some("ProtectedLight"),
+            ProcessTrustLabel::TYPE_PROTECTED => Some("Protected"),
+            _ => None,
+        }
+    }
+
+    pub fn trust_level_name(&self) -> Option<&'static str> {
+        match self.trust_level {
+            ProcessTrustLabel::LEVEL_NONE => Some("None"),
+            ProcessTrustLabel::LEVEL_AUTHENTICODE => Some("Authenticode"),
+            ProcessTrustLabel::LEVEL_ANTIMALWARE => Some("Antimalware"),
+            ProcessTrustLabel::LEVEL_APP => Some("App"),
+            ProcessTrustLabel::LEVEL_WINDOWS => Some("Windows"),
+            ProcessTrustLabel::LEVEL_WINTCB => Some("WinTcb"),
+            _ => None,
+        }
+    }
+}
+
+/// The kind of object an access mask applies to, which determines the meaning of its specific rights.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AccessContext {
+    File,
+    Directory,
+    Registry,
+    /// Only the standard rights, which have the same meaning for every object.
+    Standard,
+}
+
+fn push_flag_names<F: Flags<Bits = u32>>(names: &mut Vec<String>, flags: F) -> u32 {
+    names.extend(flags.iter_names().map(|(name, _)| name.to_string()));
+
+    flags.bits()
+}
+
+/// Serializes an access mask as both its raw value and the names of its rights, decoded as file rights
+/// since the kind of object isn't known. Only the raw value is used when deserializing.
+#[cfg(feature = "serde-access-masks")]
+mod access_mask {
+    use super::{access_rights_names, AccessContext};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize, Deserialize)]
+    #[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+    pub struct AccessMask {
+        raw: u32,
+        #[serde(default)]
+        rights: Vec<String>,
+    }
+
+    pub fn serialize<S: Serializer>(mask: &u32, serializer: S) -> Result<S::Ok, S::Error> {
+        AccessMask {
+            raw: *mask,
+            rights: access_rights_names(*mask, AccessContext::File),
+        }
+        .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u32, D::Error> {
+        AccessMask::deserialize(deserializer).map(|mask| mask.raw)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde-camelcase", serde(rename_all = "camelCase"))]
 pub struct AceObject {
+    #[cfg_attr(feature = "serde-access-masks", serde(with = "access_mask"))]
+    #[cfg_attr(
+        all(feature = "serde-access-masks", feature = "schemars"),
+        schemars(with = "access_mask::AccessMask")
+    )]
     pub access_rights: u32,
-    pub flags: u32,
-    pub object_type: Guid,
-    pub inherited_type: Guid,
+    pub flags: AceObjectFlags,
+    /// Only present when `ACE_OBJECT_TYPE_PRESENT` is set.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub object_type: Option<Guid>,
+    /// Only present when `ACE_INHERITED_OBJECT_TYPE_PRESENT` is set.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub inherited_type: Option<Guid>,
     pub sid: Sid,
+    /// The conditional expression of callback object ACEs, which follows the SID.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub application_data: Option<RawAce>,
 }
 
 impl AceObject {
     pub fn from_reader<R: Read>(mut reader: &mut R) -> Result<AceObject> {
         let access_rights = reader.read_u32::<LittleEndian>()?;
-        let flags = reader.read_u32::<LittleEndian>()?;
-        let object_type = Guid::from_reader(&mut reader)?;
-        let inherited_type = Guid::from_reader(&mut reader)?;
+        let flags = AceObjectFlags::from_bits_retain(reader.read_u32::<LittleEndian>()?);
+
+        // The GUIDs are only stored when their flag is set.
+        let object_type = if flags.contains(AceObjectFlags::ACE_OBJECT_TYPE_PRESENT) {
+            Some(Guid::from_reader(&mut reader)?)
+        } else {
+            None
+        };
+
+        let inherited_type = if flags.contains(AceObjectFlags::ACE_INHERITED_OBJECT_TYPE_PRESENT) {
+            Some(Guid::from_reader(&mut reader)?)
+        } else {
+            None
+        };
+
         let sid = Sid::from_reader(&mut reader)?;
 
         Ok(AceObject {
@@ -162,11 +686,142 @@ impl AceObject {
             object_type,
             inherited_type,
             sid,
+            application_data: None,
+        })
+    }
+
+    /// The name of `object_type`, if it is one of the Active Directory object types in `ad_rights`.
+    pub fn object_type_name(&self) -> Option<&'static str> {
+        self.object_type
+            .as_ref()
+            .and_then(ad_rights::lookup)
+            .map(|object_type| object_type.name)
+    }
+
+    /// The GUIDs are written when present, regardless of `flags`.
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_u32::<LittleEndian>(self.access_rights)?;
+        writer.write_u32::<LittleEndian>(self.flags.bits())?;
+
+        if let Some(object_type) = &self.object_type {
+            object_type.write_to(writer)?;
+        }
+
+        if let Some(inherited_type) = &self.inherited_type {
+            inherited_type.write_to(writer)?;
+        }
+
+        self.sid.write_to(writer)?;
+
+        if let Some(application_data) = &self.application_data {
+            writer.write_all(&application_data.0)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn has_object_type(&self) -> bool {
+        self.flags.contains(AceObjectFlags::ACE_OBJECT_TYPE_PRESENT)
+    }
+
+    pub fn has_inherited_object_type(&self) -> bool {
+        self.flags
+            .contains(AceObjectFlags::ACE_INHERITED_OBJECT_TYPE_PRESENT)
+    }
+}
+
+/// The body of an `ACCESS_ALLOWED_COMPOUND` ACE, which grants access to a server acting on behalf of a client.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde-camelcase", serde(rename_all = "camelCase"))]
+pub struct AceCompound {
+    #[cfg_attr(feature = "serde-access-masks", serde(with = "access_mask"))]
+    #[cfg_attr(
+        all(feature = "serde-access-masks", feature = "schemars"),
+        schemars(with = "access_mask::AccessMask")
+    )]
+    pub access_rights: u32,
+    /// `COMPOUND_ACE_IMPERSONATION` (1) is the only defined type.
+    pub compound_type: u16,
+    #[serde(skip_serializing, default)]
+    pub reserved: u16,
+    pub server_sid: Sid,
+    pub client_sid: Sid,
+}
+
+/// Like serialization, equality ignores the `reserved` field.
+impl PartialEq for AceCompound {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for AceCompound {}
+
+impl Hash for AceCompound {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key().hash(state)
+    }
+}
+
+impl AceCompound {
+    fn key(&self) -> (u32, u16, &Sid, &Sid) {
+        (
+            self.access_rights,
+            self.compound_type,
+            &self.server_sid,
+            &self.client_sid,
+        )
+    }
+
+    pub fn from_reader<R: Read>(mut reader: &mut R) -> Result<AceCompound> {
+        let access_rights = reader.read_u32::<LittleEndian>()?;
+        let compound_type = reader.read_u16::<LittleEndian>()?;
+        let reserved = reader.read_u16::<LittleEndian>()?;
+        // The server SID is variable length, so the client SID starts wherever it ends.
+        let server_sid = Sid::from_reader(&mut reader)?;
+        let client_sid = Sid::from_reader(&mut reader)?;
+
+        Ok(AceCompound {
+            access_rights,
+            compound_type,
+            reserved,
+            server_sid,
+            client_sid,
         })
     }
+
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_u32::<LittleEndian>(self.access_rights)?;
+        writer.write_u16::<LittleEndian>(self.compound_type)?;
+        writer.write_u16::<LittleEndian>(self.reserved)?;
+        self.server_sid.write_to(writer)?;
+        self.client_sid.write_to(writer)
+    }
 }
 
-#[derive(Clone)]
+/// Unparsed bytes of an ACE, such as the body (without the header) of an ACE of an unhandled type.
+///
+/// # Example
+///
+/// Decoding the body of an unhandled type which is laid out like a basic ACE:
+///
+/// ```
+/// # use std::io::Cursor;
+/// # use winstructs::security::{AceBasic, RawAce};
+/// let raw = RawAce(vec![
+///     0xFF, 0x01, 0x1F, 0x00, // access mask
+///     0x01, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x05, 0x12, 0x00, 0x00, 0x00, // S-1-5-18
+/// ]);
+///
+/// let basic = AceBasic::from_reader(&mut Cursor::new(&raw.0)).unwrap();
+///
+/// assert_eq!(basic.access_rights, 0x1F01FF);
+/// assert_eq!(basic.sid.to_string(), "S-1-5-18");
+/// ```
+#[derive(Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct RawAce(pub Vec<u8>);
 
 impl fmt::Debug for RawAce {
@@ -175,6 +830,8 @@ impl fmt::Debug for RawAce {
     }
 }
 
+impl_json_schema_as_string!(RawAce);
+
 impl ser::Serialize for RawAce {
     fn serialize<S>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error>
     where
@@ -184,18 +841,50 @@ impl ser::Serialize for RawAce {
     }
 }
 
+impl<'de> de::Deserialize<'de> for RawAce {
+    fn deserialize<D>(deserializer: D) -> ::std::result::Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        let hex = String::deserialize(deserializer)?;
+
+        Ok(RawAce(
+            utils::from_hex_string(&hex).map_err(de::Error::custom)?,
+        ))
+    }
+}
+
 bitflags! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
     pub struct AceFlags: u8 {
         const OBJECT_INHERIT_ACE            = 0x01;
         const CONTAINER_INHERIT_ACE         = 0x02;
         const NO_PROPAGATE_INHERIT_ACE      = 0x04;
         const INHERIT_ONLY_ACE              = 0x08;
+        const INHERITED_ACE                 = 0x10;
     }
 }
 
 impl_serialize_for_bitflags! {AceFlags}
+impl_deserialize_for_bitflags! {AceFlags}
+impl_arbitrary_for_bitflags! {AceFlags}
+impl_json_schema_as_string! {AceFlags}
 
 bitflags! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+    pub struct AceObjectFlags: u32 {
+        const ACE_OBJECT_TYPE_PRESENT               = 0x0000_0001;
+        const ACE_INHERITED_OBJECT_TYPE_PRESENT     = 0x0000_0002;
+    }
+}
+
+impl_serialize_for_bitflags! {AceObjectFlags}
+impl_deserialize_for_bitflags! {AceObjectFlags}
+impl_arbitrary_for_bitflags! {AceObjectFlags}
+impl_json_schema_as_string! {AceObjectFlags}
+
+bitflags! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
     pub struct StandardAccessFlags: u32 {
         // Standard access rights flags
         const SA_RIGHT_DELETE           = 0x0001_0000;
@@ -207,8 +896,12 @@ bitflags! {
 }
 
 impl_serialize_for_bitflags! {StandardAccessFlags}
+impl_deserialize_for_bitflags! {StandardAccessFlags}
+impl_arbitrary_for_bitflags! {StandardAccessFlags}
+impl_json_schema_as_string! {StandardAccessFlags}
 
 bitflags! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
     pub struct NonFolderAccessFlags: u32 {
         // Non-folder item access rights flags
         const NFA_RIGHT_READBODY                = 0x0000_0001; //FILE_READ_DATA
@@ -226,8 +919,12 @@ bitflags! {
 }
 
 impl_serialize_for_bitflags! {NonFolderAccessFlags}
+impl_deserialize_for_bitflags! {NonFolderAccessFlags}
+impl_arbitrary_for_bitflags! {NonFolderAccessFlags}
+impl_json_schema_as_string! {NonFolderAccessFlags}
 
 bitflags! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
     pub struct FolderAccessFlags: u32 {
         // Folder item access rights flags
         const FA_RIGHT_LISTCONTENTS               = 0x0000_0001; //FILE_LIST_DIRECTORY
@@ -246,3 +943,402 @@ bitflags! {
 }
 
 impl_serialize_for_bitflags! {FolderAccessFlags}
+impl_deserialize_for_bitflags! {FolderAccessFlags}
+impl_arbitrary_for_bitflags! {FolderAccessFlags}
+impl_json_schema_as_string! {FolderAccessFlags}
+
+bitflags! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+    pub struct RegistryAccessFlags: u32 {
+        // Registry key access rights flags
+        const KEY_QUERY_VALUE                     = 0x0000_0001;
+        const KEY_SET_VALUE                       = 0x0000_0002;
+        const KEY_CREATE_SUB_KEY                  = 0x0000_0004;
+        const KEY_ENUMERATE_SUB_KEYS              = 0x0000_0008;
+        const KEY_NOTIFY                          = 0x0000_0010;
+        const KEY_CREATE_LINK                     = 0x0000_0020;
+        const KEY_WOW64_64KEY                     = 0x0000_0100;
+        const KEY_WOW64_32KEY                     = 0x0000_0200;
+    }
+}
+
+impl_serialize_for_bitflags! {RegistryAccessFlags}
+impl_deserialize_for_bitflags! {RegistryAccessFlags}
+impl_arbitrary_for_bitflags! {RegistryAccessFlags}
+impl_json_schema_as_string! {RegistryAccessFlags}
+
+#[cfg(test)]
+mod tests {
+    use crate::err::Error;
+    use crate::security::ace::{
+        AccessContext, Ace, AceBasic, AceData, AceDataKind, AceFlags, AceObjectFlags, AceType,
+        ProcessTrustLabel,
+    };
+    use std::io::Cursor;
+
+    // ACCESS_ALLOWED_COMPOUND, server S-1-5-20 impersonating S-1-5-21-1-2-3-1001.
+    const COMPOUND_ACE: &[u8] = &[
+        0x04, 0x00, 0x34, 0x00, 0xFF, 0x01, 0x1F, 0x00, 0x01, 0x00, 0x00,
+        0x00, // header, mask, type
+        0x01, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x05, 0x14, 0x00, 0x00, 0x00, // S-1-5-20
+        0x01, 0x05, 0x00, 0x00, 0x00, 0x00, 0x00, 0x05, 0x15, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00,
+        0x00, 0x02, 0x00, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00, 0xE9, 0x03, 0x00,
+        0x00, // S-1-5-21-1-2-3-1001
+    ];
+
+    // ACCESS_ALLOWED_OBJECT for S-1-5-18, with only the object type present.
+    const OBJECT_ACE: &[u8] = &[
+        0x05, 0x00, 0x28, 0x00, 0x00, 0x01, 0x00, 0x00, 0x01, 0x00, 0x00,
+        0x00, // header, mask, flags
+        0x25, 0x96, 0x84, 0x54, 0x78, 0x54, 0x94, 0x49, 0xa5, 0xba, 0x3e, 0x3b, 0x03, 0x28, 0xc3,
+        0x0d, // object type
+        0x01, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x05, 0x12, 0x00, 0x00, 0x00, // S-1-5-18
+    ];
+
+    #[test]
+    fn test_parses_object_ace_with_object_type_only() {
+        let ace = Ace::from_buffer(OBJECT_ACE).unwrap();
+        assert!(matches!(ace.ace_type, AceType::AccessAllowedObject));
+        assert_eq!(ace.byte_len(), OBJECT_ACE.len());
+
+        let object = match ace.data {
+            AceData::Object(object) => object,
+            _ => panic!("expected an object ACE"),
+        };
+
+        assert_eq!(object.flags, AceObjectFlags::ACE_OBJECT_TYPE_PRESENT);
+        assert!(object.has_object_type());
+        assert!(!object.has_inherited_object_type());
+        assert_eq!(
+            object.object_type.as_ref().unwrap().to_string(),
+            "54849625-5478-4994-A5BA-3E3B0328C30D"
+        );
+        assert!(object.inherited_type.is_none());
+        assert_eq!(object.sid.to_string(), "S-1-5-18");
+
+        let json = serde_json::to_value(&object).unwrap();
+        assert_eq!(json["flags"], "ACE_OBJECT_TYPE_PRESENT");
+        let inherited_type = if cfg!(feature = "serde-camelcase") {
+            "inheritedType"
+        } else {
+            "inherited_type"
+        };
+        assert!(json.get(inherited_type).is_none());
+
+        // Not one of the well known Active Directory object types.
+        assert_eq!(object.object_type_name(), None);
+    }
+
+    #[test]
+    fn test_object_type_name() {
+        let mut buffer = OBJECT_ACE.to_vec();
+        // DS-Replication-Get-Changes, 1131F6AA-9C07-11D1-F79F-00C04FC2DCD2.
+        buffer[12..28].copy_from_slice(&[
+            0xAA, 0xF6, 0x31, 0x11, 0x07, 0x9C, 0xD1, 0x11, 0xF7, 0x9F, 0x00, 0xC0, 0x4F, 0xC2,
+            0xDC, 0xD2,
+        ]);
+
+        match Ace::from_buffer(&buffer).unwrap().data {
+            AceData::Object(object) => assert_eq!(
+                object.object_type_name(),
+                Some("DS-Replication-Get-Changes")
+            ),
+            _ => panic!("expected an object ACE"),
+        }
+    }
+
+    #[test]
+    fn test_keeps_callback_object_application_data() {
+        let mut buffer = OBJECT_ACE.to_vec();
+        // ACCESS_ALLOWED_CALLBACK_OBJECT, followed by a conditional expression.
+        buffer[0] = 0x0b;
+        buffer[2] = 0x30;
+        buffer.extend_from_slice(b"artx\x00\x00\x00\x00");
+
+        let ace = Ace::from_buffer(&buffer).unwrap();
+        assert!(ace.ace_type.is_callback());
+        assert!(ace.trailing.is_none());
+        assert_eq!(ace.byte_len(), buffer.len());
+
+        let object = match ace.data {
+            AceData::Object(object) => object,
+            _ => panic!("expected an object ACE"),
+        };
+
+        assert_eq!(object.sid.to_string(), "S-1-5-18");
+        assert_eq!(
+            object.application_data.unwrap().0,
+            b"artx\x00\x00\x00\x00".to_vec()
+        );
+
+        // Non callback object ACEs have nothing there.
+        let ace = Ace::from_buffer(OBJECT_ACE).unwrap();
+        assert!(matches!(ace.data, AceData::Object(object) if object.application_data.is_none()));
+    }
+
+    #[test]
+    fn test_keeps_callback_application_data() {
+        let mut buffer = vec![
+            0x09, 0x00, 0x1C, 0x00, 0xFF, 0x01, 0x1F, 0x00, 0x01, 0x01, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x05, 0x12, 0x00, 0x00, 0x00, // ACCESS_ALLOWED_CALLBACK for S-1-5-18
+        ];
+        buffer.extend_from_slice(b"artx\x00\x00\x00\x00");
+
+        let ace = Ace::from_buffer(&buffer).unwrap();
+        assert!(ace.trailing.is_none());
+        assert_eq!(ace.byte_len(), buffer.len());
+
+        let basic = match &ace.data {
+            AceData::Basic(basic) => basic,
+            _ => panic!("expected a basic ACE"),
+        };
+        assert_eq!(
+            basic.application_data.as_ref().unwrap().0,
+            b"artx\x00\x00\x00\x00".to_vec()
+        );
+
+        let mut written = Vec::new();
+        ace.write_to(&mut written).unwrap();
+        assert_eq!(written, buffer);
+
+        // Other basic ACEs keep the same bytes as padding.
+        buffer[0] = 0x00;
+        let ace = Ace::from_buffer(&buffer).unwrap();
+        assert!(matches!(&ace.data, AceData::Basic(basic) if basic.application_data.is_none()));
+        assert_eq!(ace.trailing.unwrap().0.len(), 8);
+    }
+
+    #[test]
+    fn test_describes_access_rights() {
+        let describe = |access_rights, context| {
+            let basic = AceBasic {
+                access_rights,
+                sid: "S-1-1-0".parse().unwrap(),
+                application_data: None,
+            };
+
+            basic.access_rights_description(context)
+        };
+
+        assert_eq!(
+            describe(0x0002_0000, AccessContext::Standard),
+            "SA_RIGHT_READCONTROL"
+        );
+        assert_eq!(
+            describe(0x0002_0001, AccessContext::File),
+            "SA_RIGHT_READCONTROL | NFA_RIGHT_READBODY"
+        );
+        assert_eq!(
+            describe(0x0002_0001, AccessContext::Directory),
+            "SA_RIGHT_READCONTROL | FA_RIGHT_LISTCONTENTS"
+        );
+        assert_eq!(
+            describe(0x0002_0019, AccessContext::Registry),
+            "SA_RIGHT_READCONTROL | KEY_QUERY_VALUE | KEY_ENUMERATE_SUB_KEYS | KEY_NOTIFY"
+        );
+        // Specific rights aren't named without a context, and GENERIC_ALL has no flag at all.
+        assert_eq!(
+            describe(0x1002_0001, AccessContext::Standard),
+            "SA_RIGHT_READCONTROL | 0x10000001"
+        );
+    }
+
+    #[test]
+    fn test_parses_process_trust_label_ace() {
+        let buffer: &[u8] = &[
+            0x14, 0x00, 0x18, 0x00, 0x03, 0x02, 0x02, 0x00, // header, mask
+            0x01, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x13, 0x00, 0x04, 0x00, 0x00, 0x00, 0x20,
+            0x00, 0x00, // S-1-19-1024-8192
+        ];
+
+        let ace = Ace::from_buffer(buffer).unwrap();
+        assert_eq!(ace.ace_type, AceType::SystemProcessTrustLabel);
+
+        let basic = match ace.data {
+            AceData::Basic(basic) => basic,
+            _ => panic!("expected a basic ACE"),
+        };
+
+        assert_eq!(basic.access_rights, 0x0002_0203);
+
+        let label = basic.process_trust_label().unwrap();
+        assert_eq!(label.trust_type, ProcessTrustLabel::TYPE_PROTECTED);
+        assert_eq!(label.trust_level, ProcessTrustLabel::LEVEL_WINTCB);
+        assert_eq!(label.trust_type_name(), Some("Protected"));
+        assert_eq!(label.trust_level_name(), Some("WinTcb"));
+
+        assert!(ProcessTrustLabel::from_sid(&"S-1-5-18".parse().unwrap()).is_none());
+    }
+
+    #[test]
+    fn test_keeps_inherited_ace_flag() {
+        // An inherited access allowed ACE for S-1-1-0.
+        let buffer: &[u8] = &[
+            0x00, 0x10, 0x14, 0x00, 0xFF, 0x01, 0x1F, 0x00, 0x01, 0x01, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x01, 0x00, 0x00, 0x00, 0x00,
+        ];
+
+        let ace = Ace::from_buffer(buffer).unwrap();
+        assert_eq!(ace.ace_flags, AceFlags::INHERITED_ACE);
+
+        let mut written = Vec::new();
+        ace.write_to(&mut written).unwrap();
+        assert_eq!(written, buffer);
+    }
+
+    #[test]
+    fn test_serializes_empty_flags_as_none() {
+        let json = serde_json::to_string(&AceFlags::empty()).unwrap();
+        assert_eq!(json, "\"NONE\"");
+        assert_eq!(
+            serde_json::from_str::<AceFlags>(&json).unwrap(),
+            AceFlags::empty()
+        );
+
+        assert_eq!(
+            serde_json::to_string(&AceFlags::OBJECT_INHERIT_ACE).unwrap(),
+            "\"OBJECT_INHERIT_ACE\""
+        );
+    }
+
+    #[test]
+    fn test_rejects_ace_smaller_than_header() {
+        // An access allowed ACE declaring a size of 2 bytes.
+        let buffer: &[u8] = &[0x00, 0x00, 0x02, 0x00, 0xFF, 0xFF, 0xFF, 0xFF];
+
+        match Ace::from_buffer(buffer) {
+            Err(Error::InvalidAceSize { size }) => assert_eq!(size, 2),
+            other => panic!("expected InvalidAceSize, got {:?}", other),
+        }
+    }
+
+    #[cfg(feature = "serde-access-masks")]
+    #[test]
+    fn test_serializes_raw_and_decoded_access_masks() {
+        let ace = Ace::from_buffer(OBJECT_ACE).unwrap();
+        let json = serde_json::to_value(&ace).unwrap();
+
+        let access_rights = if cfg!(feature = "serde-camelcase") {
+            "accessRights"
+        } else {
+            "access_rights"
+        };
+        let mask = &json["data"][access_rights];
+        assert_eq!(mask["raw"], 0x100);
+        assert_eq!(
+            mask["rights"],
+            serde_json::json!(["NFA_RIGHT_WRITEATTRIBUTES"])
+        );
+
+        assert_eq!(serde_json::from_value::<Ace>(json).unwrap(), ace);
+    }
+
+    #[test]
+    fn test_ace_serde_round_trip() {
+        let object = Ace::from_buffer(OBJECT_ACE).unwrap();
+
+        // An ACE of the unknown type 0x20, whose body is kept unparsed.
+        let unhandled = Ace::read(
+            &mut Cursor::new(&[0x20, 0x00, 0x08, 0x00, 0xDE, 0xAD, 0xBE, 0xEF]),
+            true,
+        )
+        .unwrap();
+        assert_eq!(unhandled.data.kind(), AceDataKind::Unhandled);
+
+        for ace in [object, unhandled] {
+            let json = serde_json::to_string(&ace).unwrap();
+            let deserialized: Ace = serde_json::from_str(&json).unwrap();
+
+            assert_eq!(deserialized, ace);
+            assert_eq!(deserialized.data.kind(), ace.data.kind());
+        }
+    }
+
+    #[test]
+    fn test_ace_data_kind() {
+        let mut buffer = vec![
+            0x00, 0x00, 0x14, 0x00, 0xFF, 0x01, 0x1F, 0x00, 0x01, 0x01, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x05, 0x12, 0x00, 0x00, 0x00, // ACCESS_ALLOWED S-1-5-18
+            0x30, 0x00, 0x08, 0x00, 0x01, 0x02, 0x03, 0x04, // unknown type
+        ];
+        buffer.extend_from_slice(OBJECT_ACE);
+        buffer.extend_from_slice(COMPOUND_ACE);
+        buffer.extend_from_slice(OBJECT_ACE);
+
+        let mut reader = Cursor::new(&buffer);
+        let kinds: Vec<AceDataKind> = (0..5)
+            .map(|_| Ace::read(&mut reader, true).unwrap().data.kind())
+            .collect();
+
+        let count = |kind| kinds.iter().filter(|k| **k == kind).count();
+
+        assert_eq!(count(AceDataKind::Basic), 1);
+        assert_eq!(count(AceDataKind::Object), 2);
+        assert_eq!(count(AceDataKind::Compound), 1);
+        assert_eq!(count(AceDataKind::Unhandled), 1);
+        assert_eq!(kinds[1], AceDataKind::Unhandled);
+    }
+
+    #[test]
+    fn test_sorts_aces_by_key() {
+        let basic = |ace_type: u8, access_rights: u32, sid: &str| Ace {
+            ace_type: AceType::from_u8(ace_type).unwrap(),
+            ace_flags: AceFlags::empty(),
+            size: 0,
+            data: AceData::Basic(AceBasic {
+                access_rights,
+                sid: sid.parse().unwrap(),
+                application_data: None,
+            }),
+            trailing: None,
+        };
+
+        let mut entries = vec![
+            basic(0x01, 0x1F01FF, "S-1-1-0"),
+            basic(0x00, 0x1F01FF, "S-1-5-18"),
+            basic(0x00, 0x1200A9, "S-1-5-32-545"),
+            basic(0x00, 0x1F01FF, "S-1-1-0"),
+            basic(0x00, 0x1200A9, "S-1-1-0"),
+        ];
+        entries.sort_by_cached_key(Ace::sort_key);
+
+        assert_eq!(
+            entries,
+            [
+                basic(0x00, 0x1200A9, "S-1-1-0"),
+                basic(0x00, 0x1F01FF, "S-1-1-0"),
+                basic(0x00, 0x1F01FF, "S-1-5-18"),
+                basic(0x00, 0x1200A9, "S-1-5-32-545"),
+                basic(0x01, 0x1F01FF, "S-1-1-0"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_ace_type_as_u8() {
+        assert_eq!(AceType::AccessAllowed.as_u8(), 0x00);
+        assert_eq!(AceType::SystemMandatoryLabel.as_u8(), 0x11);
+        assert_eq!(AceType::SystemAccessFilter.as_u8(), 0x15);
+    }
+
+    #[test]
+    fn test_parses_compound_ace() {
+        let ace = Ace::from_buffer(COMPOUND_ACE).unwrap();
+        assert!(matches!(ace.ace_type, AceType::AccessAllowedCompound));
+
+        let compound = match ace.data {
+            AceData::Compound(compound) => compound,
+            _ => panic!("expected a compound ACE"),
+        };
+
+        assert_eq!(compound.access_rights, 0x1F01FF);
+        assert_eq!(compound.compound_type, 1);
+        assert_eq!(compound.server_sid.to_string(), "S-1-5-20");
+        assert_eq!(compound.client_sid.to_string(), "S-1-5-21-1-2-3-1001");
+
+        assert_eq!(
+            Ace::from_buffer(COMPOUND_ACE).unwrap().byte_len(),
+            COMPOUND_ACE.len()
+        );
+    }
+}
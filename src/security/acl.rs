@@ -1,47 +1,668 @@
 //! ACL
 //! https://github.com/libyal/libfwnt/wiki/Security-Descriptor#access-control-list-acl
 
-use crate::err::Result;
-use crate::security::ace::Ace;
-use byteorder::{LittleEndian, ReadBytesExt};
-use serde::Serialize;
+use crate::err::{Error, Result};
+use crate::security::ace::{Ace, AceFlags};
+use crate::security::sec_desc::SecurityDescriptorParser;
+use crate::Bounded;
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use serde::{Deserialize, Serialize};
 
-use std::io::Read;
+use std::convert::TryFrom;
+use std::hash::{Hash, Hasher};
+use std::io::{self, Cursor, Read, Write};
+#[cfg(feature = "tokio")]
+use tokio::io::{AsyncRead, AsyncReadExt};
 
-#[derive(Serialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde-camelcase", serde(rename_all = "camelCase"))]
 pub struct Acl {
     pub revision: u8,
-    #[serde(skip_serializing)]
+    #[serde(skip_serializing, default)]
     pub padding1: u8,
-    #[serde(skip_serializing)]
+    #[serde(skip_serializing, default)]
     pub size: u16,
     pub count: u16,
-    #[serde(skip_serializing)]
+    #[serde(skip_serializing, default)]
     pub padding2: u16,
     pub entries: Vec<Ace>,
+    /// Unused space between the last entry and the declared size of the ACL.
+    #[serde(skip_serializing, default)]
+    pub slack: Vec<u8>,
+}
+
+/// Like serialization, equality ignores the padding, `size` and `slack`, which only describe the layout.
+impl PartialEq for Acl {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for Acl {}
+
+impl Hash for Acl {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key().hash(state)
+    }
 }
 
 impl Acl {
+    /// The revision of ACLs which contain only basic ACEs.
+    pub const ACL_REVISION: u8 = 2;
+    /// The revision of ACLs which may also contain object ACEs.
+    pub const ACL_REVISION_DS: u8 = 4;
+
+    fn key(&self) -> (u8, u16, &[Ace]) {
+        (self.revision, self.count, &self.entries)
+    }
+
     pub fn from_reader<R: Read>(reader: &mut R) -> Result<Acl> {
-        let revision = reader.read_u8()?;
-        let padding1 = reader.read_u8()?;
-        let size = reader.read_u16::<LittleEndian>()?;
-        let count = reader.read_u16::<LittleEndian>()?;
-        let padding2 = reader.read_u16::<LittleEndian>()?;
-        let mut entries: Vec<Ace> = Vec::with_capacity(count as usize);
+        Self::read(reader, &SecurityDescriptorParser::new())
+    }
+
+    /// Like `from_reader`, but errors if the ACL revision is not a known one.
+    pub fn from_reader_validated<R: Read>(reader: &mut R) -> Result<Acl> {
+        Self::read(
+            reader,
+            &SecurityDescriptorParser::new().validate_revisions(true),
+        )
+    }
+
+    /// Like `from_reader`, but ignores the declared `count` and reads ACEs until the declared size is used up,
+    /// which recovers more entries from a damaged ACL. Parsing stops early at an ACE whose declared size
+    /// is smaller than its header, as in zeroed slack.
+    ///
+    /// `count` is kept as declared, `validate` reports when it doesn't match the entries.
+    pub fn from_reader_by_size<R: Read>(reader: &mut R) -> Result<Acl> {
+        Self::read_with(reader, &SecurityDescriptorParser::new(), true)
+    }
 
-        for _ in 0..count {
-            let ace = Ace::from_reader(reader)?;
-            entries.push(ace);
+    pub(crate) fn read<R: Read>(reader: &mut R, parser: &SecurityDescriptorParser) -> Result<Acl> {
+        Self::read_with(reader, parser, false)
+    }
+
+    fn read_with<R: Read>(
+        reader: &mut R,
+        parser: &SecurityDescriptorParser,
+        by_size: bool,
+    ) -> Result<Acl> {
+        let validate = parser.validate_revisions;
+        let (revision, padding1, size, count, padding2) = read_header(reader, parser)?;
+
+        // The entries should never extend past the declared size of the ACL (which includes the header).
+        let entries_size = size.saturating_sub(8);
+
+        // Every ACE has at least a 4 byte header, so a larger count can't be valid.
+        // Checking this first also means `count` can't be used to make us over-allocate.
+        if !by_size && usize::from(count) > usize::from(entries_size / 4) {
+            return Err(Error::AceCountExceedsAclSize { count, size });
         }
 
-        Ok(Acl {
+        let mut entries_reader = Bounded::new(reader, u64::from(entries_size));
+
+        let (entries, slack) = if by_size {
+            read_entries_by_size(&mut entries_reader, parser.tolerate_unknown_ace_types)?
+        } else {
+            let mut entries: Vec<Ace> = Vec::with_capacity(usize::from(count));
+
+            for _ in 0..count {
+                let ace = Ace::read(&mut entries_reader, parser.tolerate_unknown_ace_types)?;
+                entries.push(ace);
+            }
+
+            // ACLs are often allocated larger than needed, keep the rest so it can be written back.
+            // A stream which ends before the declared size is not an error here.
+            let mut slack = Vec::new();
+            entries_reader.read_to_end(&mut slack)?;
+
+            (entries, slack)
+        };
+
+        let acl = Acl {
             revision,
             padding1,
             size,
             count,
             padding2,
             entries,
+            slack,
+        };
+
+        if validate {
+            acl.validate_ace_revisions()?;
+        }
+
+        Ok(acl)
+    }
+
+    /// Checks that every entry is allowed under the revision of this ACL.
+    /// Object ACEs require `ACL_REVISION_DS`, while basic ACEs may appear under either revision.
+    pub fn validate_ace_revisions(&self) -> Result<()> {
+        self.entries
+            .iter()
+            .try_for_each(|ace| check_ace_revision(ace, self.revision))
+    }
+
+    pub fn from_buffer(buffer: &[u8]) -> Result<Self> {
+        Self::from_reader(&mut Cursor::new(buffer))
+    }
+
+    /// Checks that the declared size of the ACL matches the header plus the declared sizes of its ACEs,
+    /// and that the declared count matches the number of entries.
+    /// Since ACLs are DWORD aligned, up to 3 bytes of slack are allowed.
+    ///
+    /// The size is checked first, so a size mismatch is reported even if the count is wrong as well.
+    pub fn validate(&self) -> Result<()> {
+        let actual = 8 + self
+            .entries
+            .iter()
+            .map(|ace| usize::from(ace.size))
+            .sum::<usize>();
+        let declared = usize::from(self.size);
+
+        if actual > declared || declared - actual > 3 {
+            return Err(Error::AclSizeMismatch {
+                declared: self.size,
+                actual,
+            });
+        }
+
+        if usize::from(self.count) != self.entries.len() {
+            return Err(Error::AceCountMismatch {
+                declared: self.count,
+                parsed: self.entries.len(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Whether the entries are in the canonical order Windows expects of a DACL:
+    /// explicit deny, explicit allow, inherited deny and then inherited allow.
+    pub fn is_canonical(&self) -> bool {
+        self.entries
+            .windows(2)
+            .all(|pair| canonical_rank(&pair[0]) <= canonical_rank(&pair[1]))
+    }
+
+    /// Reorders the entries into canonical order, see `is_canonical`.
+    /// The relative order of entries within each group is kept.
+    pub fn canonicalize(&mut self) {
+        self.entries.sort_by_key(canonical_rank);
+    }
+
+    /// The size of the ACL in bytes: the 8 byte header followed by the entries and any slack.
+    pub fn byte_len(&self) -> usize {
+        8 + self.entries.iter().map(Ace::byte_len).sum::<usize>() + self.slack.len()
+    }
+
+    /// Writes the ACL in its binary form, the inverse of `from_reader`.
+    /// The size and count in the header are computed from the contents rather than taken from the fields.
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> Result<()> {
+        let too_large = || {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "ACL is too large to be written",
+            )
+        };
+        let size = u16::try_from(self.byte_len()).map_err(|_| too_large())?;
+        let count = u16::try_from(self.entries.len()).map_err(|_| too_large())?;
+
+        writer.write_u8(self.revision)?;
+        writer.write_u8(self.padding1)?;
+        writer.write_u16::<LittleEndian>(size)?;
+        writer.write_u16::<LittleEndian>(count)?;
+        writer.write_u16::<LittleEndian>(self.padding2)?;
+
+        for ace in &self.entries {
+            ace.write_to(writer)?;
+        }
+
+        writer.write_all(&self.slack)?;
+
+        Ok(())
+    }
+
+    /// Reads the ACL header, and returns an iterator which lazily reads the ACEs it declares.
+    /// Like `from_reader`, the ACEs are bounded by the declared size of the ACL.
+    pub fn iter_reader<R: Read>(reader: &mut R) -> Result<AceIter<'_, R>> {
+        Self::iter_reader_with(reader, &SecurityDescriptorParser::new())
+    }
+
+    pub(crate) fn iter_reader_with<'a, R: Read>(
+        reader: &'a mut R,
+        parser: &SecurityDescriptorParser,
+    ) -> Result<AceIter<'a, R>> {
+        let (revision, _, size, count, _) = read_header(reader, parser)?;
+        let entries_size = size.saturating_sub(8);
+
+        if usize::from(count) > usize::from(entries_size / 4) {
+            return Err(Error::AceCountExceedsAclSize { count, size });
+        }
+
+        Ok(AceIter {
+            reader: Bounded::new(reader, u64::from(entries_size)),
+            remaining: count,
+            revision,
+            parser: *parser,
         })
     }
+
+    /// Reads the ACL header, and then the rest of the ACL according to its declared size,
+    /// which is then parsed in memory.
+    #[cfg(feature = "tokio")]
+    pub async fn from_async_reader<R: AsyncRead + Unpin>(reader: &mut R) -> Result<Acl> {
+        let mut buffer = vec![0; 8];
+        reader.read_exact(&mut buffer).await?;
+
+        let size = u16::from_le_bytes([buffer[2], buffer[3]]);
+        buffer.resize(std::cmp::max(usize::from(size), 8), 0);
+        reader.read_exact(&mut buffer[8..]).await?;
+
+        Self::from_buffer(&buffer)
+    }
+}
+
+/// Reads ACEs until fewer bytes than an ACE header are left, or the next ACE declares a size
+/// smaller than its header. Returns the entries and whatever is left as slack.
+fn check_ace_revision(ace: &Ace, revision: u8) -> Result<()> {
+    if ace.ace_type.is_object() && revision != Acl::ACL_REVISION_DS {
+        return Err(Error::AceIncompatibleWithAclRevision {
+            ace_type: ace.ace_type.as_u8(),
+            revision,
+        });
+    }
+
+    Ok(())
+}
+
+/// Reads the revision, the first padding byte, the size, the count and the second padding of an ACL.
+fn read_header<R: Read>(
+    reader: &mut R,
+    parser: &SecurityDescriptorParser,
+) -> Result<(u8, u8, u16, u16, u16)> {
+    let revision = reader.read_u8()?;
+
+    if parser.validate_revisions
+        && revision != Acl::ACL_REVISION
+        && revision != Acl::ACL_REVISION_DS
+    {
+        return Err(Error::UnsupportedRevision {
+            structure: "ACL",
+            found: revision,
+        });
+    }
+
+    let padding1 = reader.read_u8()?;
+    let size = reader.read_u16::<LittleEndian>()?;
+    let count = reader.read_u16::<LittleEndian>()?;
+    let padding2 = reader.read_u16::<LittleEndian>()?;
+
+    Ok((revision, padding1, size, count, padding2))
+}
+
+fn read_entries_by_size<R: Read>(reader: &mut R, tolerate: bool) -> Result<(Vec<Ace>, Vec<u8>)> {
+    let mut rest = Vec::new();
+    reader.read_to_end(&mut rest)?;
+
+    let mut entries = Vec::new();
+    let mut offset = 0;
+
+    while let Some(header) = rest.get(offset..offset + 4) {
+        if u16::from_le_bytes([header[2], header[3]]) < 4 {
+            break;
+        }
+
+        let mut cursor = Cursor::new(&rest[offset..]);
+        entries.push(Ace::read(&mut cursor, tolerate)?);
+        offset += cursor.position() as usize;
+    }
+
+    Ok((entries, rest.split_off(offset)))
+}
+
+/// Object ACEs are ranked like their basic counterparts, and anything which doesn't deny access like an allow.
+fn canonical_rank(ace: &Ace) -> u8 {
+    let inherited = ace.ace_flags.contains(AceFlags::INHERITED_ACE);
+
+    match (inherited, ace.ace_type.is_access_denied()) {
+        (false, true) => 0,
+        (false, false) => 1,
+        (true, true) => 2,
+        (true, false) => 3,
+    }
+}
+
+/// Yields the ACEs of an ACL one at a time, see `Acl::iter_reader`.
+/// Iteration stops after the first error.
+pub struct AceIter<'a, R: Read> {
+    reader: Bounded<&'a mut R>,
+    remaining: u16,
+    revision: u8,
+    parser: SecurityDescriptorParser,
+}
+
+impl<'a, R: Read> Iterator for AceIter<'a, R> {
+    type Item = Result<Ace>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        let ace =
+            Ace::read(&mut self.reader, self.parser.tolerate_unknown_ace_types).and_then(|ace| {
+                if self.parser.validate_revisions {
+                    check_ace_revision(&ace, self.revision)?;
+                }
+
+                Ok(ace)
+            });
+
+        self.remaining = if ace.is_ok() { self.remaining - 1 } else { 0 };
+
+        Some(ace)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(usize::from(self.remaining)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::err::Error;
+    use crate::security::ace::{AceData, AceFlags, AceType};
+    use crate::security::acl::Acl;
+    use crate::security::sec_desc::SecurityDescriptorParser;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_parses_acl_with_padded_ace() {
+        let buffer: &[u8] = &[
+            0x02, 0x00, 0x34, 0x00, 0x02, 0x00, 0x00, 0x00, // header
+            0x00, 0x00, 0x18, 0x00, 0xFF, 0x01, 0x1F, 0x00, 0x01, 0x01, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x05, 0x12, 0x00, 0x00, 0x00, 0xAA, 0xAA, 0xAA, 0xAA, // S-1-5-18 + padding
+            0x01, 0x00, 0x14, 0x00, 0x00, 0x00, 0x01, 0x00, 0x01, 0x01, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x01, 0x00, 0x00, 0x00, 0x00, // S-1-1-0
+        ];
+
+        let acl = Acl::from_buffer(buffer).unwrap();
+        assert_eq!(acl.entries.len(), 2);
+
+        let sids: Vec<String> = acl
+            .entries
+            .iter()
+            .map(|ace| match &ace.data {
+                AceData::Basic(basic) => basic.sid.to_string(),
+                _ => panic!("expected a basic ACE"),
+            })
+            .collect();
+
+        assert_eq!(sids, vec!["S-1-5-18", "S-1-1-0"]);
+
+        let trailing = acl.entries[0].trailing.as_ref().unwrap();
+        assert_eq!(trailing.0, vec![0xAA; 4]);
+        assert!(acl.entries[1].trailing.is_none());
+
+        let json = serde_json::to_value(&acl.entries[0]).unwrap();
+        assert_eq!(json["trailing"], "AAAAAAAA");
+        assert!(serde_json::to_value(&acl.entries[1])
+            .unwrap()
+            .get("trailing")
+            .is_none());
+    }
+
+    #[test]
+    fn test_round_trips_acl_with_slack() {
+        let buffer: &[u8] = &[
+            0x02, 0x00, 0x40, 0x00, 0x02, 0x00, 0x00, 0x00, // header
+            0x00, 0x00, 0x18, 0x00, 0xFF, 0x01, 0x1F, 0x00, 0x01, 0x01, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x05, 0x12, 0x00, 0x00, 0x00, 0xAA, 0xAA, 0xAA, 0xAA, // S-1-5-18 + padding
+            0x01, 0x00, 0x14, 0x00, 0x00, 0x00, 0x01, 0x00, 0x01, 0x01, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x01, 0x00, 0x00, 0x00, 0x00, // S-1-1-0
+            0x00, 0x00, 0x00, 0x00, 0xBB, 0xBB, 0xBB, 0xBB, 0xBB, 0xBB, 0xBB, 0xBB, // slack
+        ];
+
+        let acl = Acl::from_buffer(buffer).unwrap();
+        assert_eq!(acl.entries.len(), 2);
+        assert_eq!(acl.slack.len(), 12);
+        assert_eq!(acl.byte_len(), buffer.len());
+
+        let mut written = Vec::new();
+        acl.write_to(&mut written).unwrap();
+        assert_eq!(written, buffer);
+    }
+
+    #[test]
+    fn test_iterates_aces_lazily() {
+        let buffer: &[u8] = &[
+            0x02, 0x00, 0x34, 0x00, 0x02, 0x00, 0x00, 0x00, // header
+            0x00, 0x00, 0x18, 0x00, 0xFF, 0x01, 0x1F, 0x00, 0x01, 0x01, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x05, 0x12, 0x00, 0x00, 0x00, 0xAA, 0xAA, 0xAA, 0xAA, // S-1-5-18 + padding
+            0x01, 0x00, 0x14, 0x00, 0x00, 0x00, 0x01, 0x00, 0x01, 0x01, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x01, 0x00, 0x00, 0x00, 0x00, // S-1-1-0
+        ];
+
+        let eager = Acl::from_buffer(buffer).unwrap();
+        assert_eq!(eager.byte_len(), buffer.len());
+
+        let mut reader = Cursor::new(buffer);
+        let lazy = Acl::iter_reader(&mut reader)
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert_eq!(
+            serde_json::to_value(&lazy).unwrap(),
+            serde_json::to_value(&eager.entries).unwrap()
+        );
+
+        // A count larger than the ACEs in the declared size yields an error, and then stops,
+        // rather than reading into whatever follows the ACL.
+        let mut buffer = buffer.to_vec();
+        buffer[4] = 3;
+        buffer.extend_from_within(8..28);
+
+        let mut reader = Cursor::new(&buffer);
+        let results: Vec<_> = Acl::iter_reader(&mut reader).unwrap().collect();
+        assert_eq!(results.len(), 3);
+        assert!(results[2].is_err());
+        assert!(Acl::from_buffer(&buffer).is_err());
+    }
+
+    #[test]
+    fn test_iterates_aces_like_eager_parsing() {
+        let buffer: &[u8] = &[
+            0x02, 0x00, 0x1C, 0x00, 0x01, 0x00, 0x00, 0x00, // header
+            0x42, 0x00, 0x14, 0x00, 0xFF, 0x01, 0x1F, 0x00, 0x01, 0x01, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x05, 0x12, 0x00, 0x00, 0x00, // an unknown ACE type
+        ];
+
+        assert!(Acl::iter_reader(&mut Cursor::new(buffer))
+            .unwrap()
+            .all(|ace| ace.is_err()));
+
+        let parser = SecurityDescriptorParser::new().tolerate_unknown_ace_types(true);
+        let eager = Acl::read(&mut Cursor::new(buffer), &parser).unwrap();
+        let lazy = parser
+            .iter_acl(&mut Cursor::new(buffer))
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert_eq!(lazy, eager.entries);
+        assert_eq!(lazy[0].ace_type, AceType::Unknown(0x42));
+    }
+
+    #[test]
+    fn test_validates_acl_size() {
+        let mut buffer = vec![
+            0x02, 0x00, 0x20, 0x00, 0x01, 0x00, 0x00, 0x00, // header
+            0x00, 0x00, 0x14, 0x00, 0x00, 0x00, 0x01, 0x00, 0x01, 0x01, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x01, 0x00, 0x00, 0x00, 0x00, // S-1-1-0
+        ];
+        // Leftover space at the end of the ACL, past the last ACE.
+        buffer.extend_from_slice(&[0; 4]);
+
+        let acl = Acl::from_buffer(&buffer).unwrap();
+        assert!(matches!(
+            acl.validate(),
+            Err(Error::AclSizeMismatch {
+                declared: 0x20,
+                actual: 0x1C
+            })
+        ));
+
+        buffer[2] = 0x1C;
+        assert!(Acl::from_buffer(&buffer).unwrap().validate().is_ok());
+    }
+
+    #[test]
+    fn test_reads_entries_by_size() {
+        let mut buffer = vec![
+            0x02, 0x00, 0x38, 0x00, 0x01, 0x00, 0x00, 0x00, // header, with a count of 1
+            0x00, 0x00, 0x14, 0x00, 0xFF, 0x01, 0x1F, 0x00, 0x01, 0x01, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x05, 0x12, 0x00, 0x00, 0x00, // S-1-5-18
+            0x01, 0x00, 0x14, 0x00, 0x00, 0x00, 0x01, 0x00, 0x01, 0x01, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x01, 0x00, 0x00, 0x00, 0x00, // S-1-1-0
+            0x00, 0x00, 0x00, 0x00, 0xBB, 0xBB, 0xBB, 0xBB, // slack
+        ];
+
+        let acl = Acl::from_buffer(&buffer).unwrap();
+        assert_eq!(acl.entries.len(), 1);
+
+        let acl = Acl::from_reader_by_size(&mut Cursor::new(&buffer)).unwrap();
+        assert_eq!(acl.count, 1);
+        assert_eq!(acl.entries.len(), 2);
+        assert_eq!(acl.entries[1].ace_type, AceType::AccessDenied);
+        assert_eq!(acl.slack, [0x00, 0x00, 0x00, 0x00, 0xBB, 0xBB, 0xBB, 0xBB]);
+        // Both the size and the count are wrong, and the size is reported first.
+        assert!(matches!(
+            acl.validate(),
+            Err(Error::AclSizeMismatch {
+                declared: 0x38,
+                actual: 0x30
+            })
+        ));
+
+        buffer[2] = 0x30;
+        let acl = Acl::from_reader_by_size(&mut Cursor::new(&buffer)).unwrap();
+        assert!(matches!(
+            acl.validate(),
+            Err(Error::AceCountMismatch {
+                declared: 1,
+                parsed: 2
+            })
+        ));
+    }
+
+    #[test]
+    fn test_rejects_count_larger_than_size() {
+        let buffer: &[u8] = &[0x02, 0x00, 0x10, 0x00, 0xFF, 0xFF, 0x00, 0x00];
+
+        assert!(matches!(
+            Acl::from_buffer(buffer),
+            Err(Error::AceCountExceedsAclSize {
+                count: 0xFFFF,
+                size: 0x10
+            })
+        ));
+    }
+
+    #[test]
+    fn test_validates_acl_revision() {
+        let buffer: &[u8] = &[0x55, 0x00, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00];
+
+        assert!(Acl::from_buffer(buffer).is_ok());
+
+        let err = Acl::from_reader_validated(&mut Cursor::new(buffer)).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::UnsupportedRevision {
+                structure: "ACL",
+                found: 0x55
+            }
+        ));
+    }
+
+    #[test]
+    fn test_rejects_object_ace_in_revision_2_acl() {
+        let mut buffer: Vec<u8> = vec![
+            0x02, 0x00, 0x40, 0x00, 0x01, 0x00, 0x00, 0x00, // header
+            0x05, 0x00, 0x38, 0x00, 0xFF, 0x01, 0x1F, 0x00, 0x03, 0x00, 0x00, 0x00,
+        ];
+        // object type and inherited object type
+        buffer.extend_from_slice(&[0x11; 32]);
+        // S-1-5-18
+        buffer.extend_from_slice(&[
+            0x01, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x05, 0x12, 0x00, 0x00, 0x00,
+        ]);
+
+        let acl = Acl::from_buffer(&buffer).unwrap();
+        assert!(matches!(acl.entries[0].data, AceData::Object(_)));
+
+        assert!(matches!(
+            Acl::from_reader_validated(&mut Cursor::new(&buffer)),
+            Err(Error::AceIncompatibleWithAclRevision {
+                ace_type: 0x05,
+                revision: 2
+            })
+        ));
+
+        buffer[0] = Acl::ACL_REVISION_DS;
+        assert!(Acl::from_reader_validated(&mut Cursor::new(&buffer)).is_ok());
+    }
+
+    #[test]
+    fn test_canonicalizes_dacl() {
+        let mut buffer: Vec<u8> = vec![
+            0x04, 0x00, 0x5C, 0x00, 0x04, 0x00, 0x00, 0x00, // header
+            0x00, 0x10, 0x14, 0x00, 0x00, 0x00, 0x01, 0x00, // inherited allow
+            0x00, 0x00, 0x14, 0x00, 0x00, 0x00, 0x01, 0x00, // explicit allow
+            0x06, 0x10, 0x18, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00,
+            0x00, // inherited deny object, without GUIDs
+            0x01, 0x00, 0x14, 0x00, 0x00, 0x00, 0x01, 0x00, // explicit deny
+        ];
+
+        // Every ACE is for S-1-1-0.
+        for offset in [16, 36, 60, 80] {
+            buffer.splice(
+                offset..offset,
+                [
+                    0x01, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00,
+                ],
+            );
+        }
+
+        let mut acl = Acl::from_buffer(&buffer).unwrap();
+        assert!(acl.entries[0].ace_flags.contains(AceFlags::INHERITED_ACE));
+        assert!(!acl.is_canonical());
+
+        acl.canonicalize();
+        assert!(acl.is_canonical());
+
+        let order: Vec<(AceType, bool)> = acl
+            .entries
+            .iter()
+            .map(|ace| {
+                (
+                    ace.ace_type,
+                    ace.ace_flags.contains(AceFlags::INHERITED_ACE),
+                )
+            })
+            .collect();
+
+        assert_eq!(
+            order,
+            vec![
+                (AceType::AccessDenied, false),
+                (AceType::AccessAllowed, false),
+                (AceType::AccessDeniedObject, true),
+                (AceType::AccessAllowed, true),
+            ]
+        );
+    }
 }
@@ -0,0 +1,175 @@
+//! Well known Active Directory object types, as referenced by object ACEs.
+//! https://docs.microsoft.com/en-us/windows/win32/adschema/extended-rights
+//!
+//! This is a small table of commonly seen control access rights, validated writes,
+//! property sets and classes, not the full schema.
+//!
+//! ```
+//! # use winstructs::guid::Guid;
+//! # use winstructs::security::ad_rights;
+//! let guid: Guid = "1131F6AA-9C07-11D1-F79F-00C04FC2DCD2".parse().unwrap();
+//!
+//! assert_eq!(ad_rights::lookup(&guid).unwrap().name, "DS-Replication-Get-Changes");
+//! ```
+use crate::guid;
+use crate::guid::Guid;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AdObjectType {
+    pub name: &'static str,
+    pub guid: Guid,
+}
+
+impl AdObjectType {
+    const fn new(name: &'static str, guid: Guid) -> Self {
+        AdObjectType { name, guid }
+    }
+}
+
+// Control access rights.
+pub const DS_REPLICATION_GET_CHANGES: AdObjectType = AdObjectType::new(
+    "DS-Replication-Get-Changes",
+    guid!("1131F6AA-9C07-11D1-F79F-00C04FC2DCD2"),
+);
+pub const DS_REPLICATION_GET_CHANGES_ALL: AdObjectType = AdObjectType::new(
+    "DS-Replication-Get-Changes-All",
+    guid!("1131F6AD-9C07-11D1-F79F-00C04FC2DCD2"),
+);
+pub const DS_REPLICATION_GET_CHANGES_IN_FILTERED_SET: AdObjectType = AdObjectType::new(
+    "DS-Replication-Get-Changes-In-Filtered-Set",
+    guid!("89E95B76-444D-4C62-991A-0FACBEDA640C"),
+);
+pub const DS_REPLICATION_SYNCHRONIZE: AdObjectType = AdObjectType::new(
+    "DS-Replication-Synchronize",
+    guid!("1131F6AB-9C07-11D1-F79F-00C04FC2DCD2"),
+);
+pub const DS_REPLICATION_MANAGE_TOPOLOGY: AdObjectType = AdObjectType::new(
+    "DS-Replication-Manage-Topology",
+    guid!("1131F6AC-9C07-11D1-F79F-00C04FC2DCD2"),
+);
+pub const USER_FORCE_CHANGE_PASSWORD: AdObjectType = AdObjectType::new(
+    "User-Force-Change-Password",
+    guid!("00299570-246D-11D0-A768-00AA006E0529"),
+);
+pub const USER_CHANGE_PASSWORD: AdObjectType = AdObjectType::new(
+    "User-Change-Password",
+    guid!("AB721A53-1E2F-11D0-9819-00AA0040529B"),
+);
+pub const SEND_AS: AdObjectType =
+    AdObjectType::new("Send-As", guid!("AB721A54-1E2F-11D0-9819-00AA0040529B"));
+pub const RECEIVE_AS: AdObjectType =
+    AdObjectType::new("Receive-As", guid!("AB721A56-1E2F-11D0-9819-00AA0040529B"));
+pub const APPLY_GROUP_POLICY: AdObjectType = AdObjectType::new(
+    "Apply-Group-Policy",
+    guid!("EDACFD8F-FFB3-11D1-B41D-00A0C968F939"),
+);
+pub const ALLOWED_TO_AUTHENTICATE: AdObjectType = AdObjectType::new(
+    "Allowed-To-Authenticate",
+    guid!("68B1D179-0D15-4D4F-AB71-46152E79A7BC"),
+);
+pub const REANIMATE_TOMBSTONES: AdObjectType = AdObjectType::new(
+    "Reanimate-Tombstones",
+    guid!("45EC5156-DB7E-47BB-B53F-DBEB2D03C40F"),
+);
+pub const UNEXPIRE_PASSWORD: AdObjectType = AdObjectType::new(
+    "Unexpire-Password",
+    guid!("CCC2DC7D-A6AD-4A7A-8846-C04E3CC53501"),
+);
+
+// Validated writes.
+pub const SELF_MEMBERSHIP: AdObjectType = AdObjectType::new(
+    "Self-Membership",
+    guid!("BF9679C0-0DE6-11D0-A285-00AA003049E2"),
+);
+pub const VALIDATED_SPN: AdObjectType = AdObjectType::new(
+    "Validated-SPN",
+    guid!("F3A64788-5306-11D1-A9C5-0000F80367C1"),
+);
+pub const VALIDATED_DNS_HOST_NAME: AdObjectType = AdObjectType::new(
+    "Validated-DNS-Host-Name",
+    guid!("72E39547-7B18-11D1-ADEF-00C04FD8D5CD"),
+);
+
+// Property sets.
+pub const GENERAL_INFORMATION: AdObjectType = AdObjectType::new(
+    "General-Information",
+    guid!("59BA2F42-79A2-11D0-9020-00C04FC2D3CF"),
+);
+pub const PERSONAL_INFORMATION: AdObjectType = AdObjectType::new(
+    "Personal-Information",
+    guid!("77B5B886-944A-11D1-AEBD-0000F80367C1"),
+);
+pub const PUBLIC_INFORMATION: AdObjectType = AdObjectType::new(
+    "Public-Information",
+    guid!("E48D0154-BCF8-11D1-8702-00C04FB96050"),
+);
+pub const USER_ACCOUNT_RESTRICTIONS: AdObjectType = AdObjectType::new(
+    "User-Account-Restrictions",
+    guid!("4C164200-20C0-11D0-A768-00AA006E0529"),
+);
+pub const USER_LOGON: AdObjectType =
+    AdObjectType::new("User-Logon", guid!("5F202010-79A5-11D0-9020-00C04FC2D4CF"));
+pub const MEMBERSHIP: AdObjectType =
+    AdObjectType::new("Membership", guid!("BC0AC240-79A9-11D0-9020-00C04FC2D4CF"));
+
+// Classes.
+pub const USER: AdObjectType =
+    AdObjectType::new("User", guid!("BF967ABA-0DE6-11D0-A285-00AA003049E2"));
+pub const GROUP: AdObjectType =
+    AdObjectType::new("Group", guid!("BF967A9C-0DE6-11D0-A285-00AA003049E2"));
+pub const COMPUTER: AdObjectType =
+    AdObjectType::new("Computer", guid!("BF967A86-0DE6-11D0-A285-00AA003049E2"));
+
+pub const ALL: &[AdObjectType] = &[
+    DS_REPLICATION_GET_CHANGES,
+    DS_REPLICATION_GET_CHANGES_ALL,
+    DS_REPLICATION_GET_CHANGES_IN_FILTERED_SET,
+    DS_REPLICATION_SYNCHRONIZE,
+    DS_REPLICATION_MANAGE_TOPOLOGY,
+    USER_FORCE_CHANGE_PASSWORD,
+    USER_CHANGE_PASSWORD,
+    SEND_AS,
+    RECEIVE_AS,
+    APPLY_GROUP_POLICY,
+    ALLOWED_TO_AUTHENTICATE,
+    REANIMATE_TOMBSTONES,
+    UNEXPIRE_PASSWORD,
+    SELF_MEMBERSHIP,
+    VALIDATED_SPN,
+    VALIDATED_DNS_HOST_NAME,
+    GENERAL_INFORMATION,
+    PERSONAL_INFORMATION,
+    PUBLIC_INFORMATION,
+    USER_ACCOUNT_RESTRICTIONS,
+    USER_LOGON,
+    MEMBERSHIP,
+    USER,
+    GROUP,
+    COMPUTER,
+];
+
+/// Finds the well known object type with the GUID `guid`, if any.
+pub fn lookup(guid: &Guid) -> Option<&'static AdObjectType> {
+    ALL.iter().find(|object_type| object_type.guid == *guid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_object_type_guids_are_unique() {
+        let guids: HashSet<&Guid> = ALL.iter().map(|object_type| &object_type.guid).collect();
+
+        assert_eq!(guids.len(), ALL.len());
+    }
+
+    #[test]
+    fn test_looks_up_object_type() {
+        let guid: Guid = "1131f6ad-9c07-11d1-f79f-00c04fc2dcd2".parse().unwrap();
+
+        assert_eq!(lookup(&guid), Some(&DS_REPLICATION_GET_CHANGES_ALL));
+        assert_eq!(lookup(&Guid::new(0, 0, 0, [0; 8])), None);
+    }
+}
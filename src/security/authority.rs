@@ -1,24 +1,37 @@
-use crate::err::Result;
-use byteorder::{BigEndian, LittleEndian, ReadBytesExt};
-use serde::Serialize;
+use crate::err::{Error, Result};
+use crate::ReadExt;
+use byteorder::{BigEndian, ByteOrder, LittleEndian, ReadBytesExt};
+use serde::{Deserialize, Serialize};
 
 use std::fmt;
-use std::io::{Cursor, Read};
+use std::io::{self, Cursor, Read};
+use std::ops::Index;
 
-#[derive(Serialize, Debug, Clone, PartialOrd, PartialEq)]
-pub struct Authority(u64);
+#[derive(Serialize, Deserialize, Debug, Clone, PartialOrd, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct Authority(pub(crate) u64);
 
 impl Authority {
     pub fn from_buffer(buffer: &[u8]) -> Result<Self> {
         Self::from_reader(&mut Cursor::new(buffer))
     }
 
+    /// The authority is a 48 bit big endian integer.
     #[inline]
     pub fn from_reader<R: Read>(reader: &mut R) -> Result<Authority> {
-        let id_high = reader.read_u32::<BigEndian>()?;
-        let id_low = reader.read_u16::<BigEndian>()?;
+        Self::from_reader_endian::<R, BigEndian>(reader)
+    }
+
+    /// Reads an authority stored with the byte order `B`, for data written by tools that got it wrong.
+    #[inline]
+    pub fn from_reader_endian<R: Read, B: ByteOrder>(reader: &mut R) -> Result<Authority> {
+        let bytes = reader.read_byte_array::<6>()?;
 
-        Ok(Authority(u64::from((id_high as u16) ^ (id_low))))
+        Ok(Authority(B::read_u48(&bytes)))
+    }
+
+    pub fn value(&self) -> u64 {
+        self.0
     }
 }
 
@@ -28,24 +41,88 @@ impl fmt::Display for Authority {
     }
 }
 
-#[derive(Serialize, Debug, Clone, PartialOrd, PartialEq)]
-pub struct SubAuthorityList(Vec<SubAuthority>);
+// Most SIDs have no more than 8 sub authorities, so these can usually be stored inline.
+#[cfg(feature = "smallvec")]
+pub(crate) type SubAuthorities = smallvec::SmallVec<[SubAuthority; 8]>;
+#[cfg(not(feature = "smallvec"))]
+pub(crate) type SubAuthorities = Vec<SubAuthority>;
+
+#[derive(Serialize, Debug, Clone, PartialOrd, PartialEq, Eq, Hash)]
+pub struct SubAuthorityList(pub(crate) SubAuthorities);
 
 impl SubAuthorityList {
     pub fn from_buffer(buffer: &[u8], count: u8) -> Result<Self> {
         Self::from_reader(&mut Cursor::new(buffer), count)
     }
 
+    /// Errors with `TruncatedSid` if the reader runs out before `count` sub authorities were read.
     #[inline]
     pub fn from_reader<R: Read>(buffer: &mut R, count: u8) -> Result<SubAuthorityList> {
-        let mut list: Vec<SubAuthority> = Vec::with_capacity(count as usize);
-
-        for _ in 0..count {
-            list.push(SubAuthority::from_reader(buffer)?)
+        let mut list = SubAuthorities::with_capacity(count as usize);
+
+        for read in 0..usize::from(count) {
+            match buffer.read_u32::<LittleEndian>() {
+                Ok(value) => list.push(SubAuthority(value)),
+                Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => {
+                    return Err(Error::TruncatedSid {
+                        read,
+                        expected: count,
+                    })
+                }
+                Err(err) => return Err(err.into()),
+            }
         }
 
         Ok(SubAuthorityList(list))
     }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn get(&self, index: usize) -> Option<&SubAuthority> {
+        self.0.get(index)
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, SubAuthority> {
+        self.0.iter()
+    }
+
+    /// Every sub authority prefixed with a `-`, which is the form used after the authority in a SID string.
+    /// This is the same as the `Display` implementation.
+    pub fn to_dashed_string(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl Index<usize> for SubAuthorityList {
+    type Output = SubAuthority;
+
+    fn index(&self, index: usize) -> &SubAuthority {
+        &self.0[index]
+    }
+}
+
+impl<'a> IntoIterator for &'a SubAuthorityList {
+    type Item = &'a SubAuthority;
+    type IntoIter = std::slice::Iter<'a, SubAuthority>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for SubAuthorityList {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        let list: Vec<SubAuthority> = u.arbitrary()?;
+
+        Ok(SubAuthorityList(list.into_iter().collect()))
+    }
 }
 
 impl fmt::Display for SubAuthorityList {
@@ -58,8 +135,9 @@ impl fmt::Display for SubAuthorityList {
     }
 }
 
-#[derive(Serialize, Debug, Clone, PartialOrd, PartialEq)]
-pub struct SubAuthority(u32);
+#[derive(Serialize, Deserialize, Debug, Clone, PartialOrd, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct SubAuthority(pub(crate) u32);
 
 impl SubAuthority {
     pub fn from_buffer(buffer: &[u8]) -> Result<Self> {
@@ -70,6 +148,10 @@ impl SubAuthority {
     pub fn from_reader<R: Read>(buffer: &mut R) -> Result<SubAuthority> {
         Ok(SubAuthority(buffer.read_u32::<LittleEndian>()?))
     }
+
+    pub fn value(&self) -> u32 {
+        self.0
+    }
 }
 
 impl fmt::Display for SubAuthority {
@@ -86,16 +168,35 @@ mod tests {
     fn test_parse_authority() {
         let buffer: &[u8] = &[0x00, 0x00, 0x00, 0x00, 0x00, 0x05];
 
-        let authority = Authority::from_buffer(&buffer).unwrap();
+        let authority = Authority::from_buffer(buffer).unwrap();
         assert_eq!(authority.0, 5);
+        assert_eq!(authority.value(), 5);
+
+        let buffer: &[u8] = &[0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC];
+        assert_eq!(
+            Authority::from_buffer(buffer).unwrap().value(),
+            0x1234_5678_9ABC
+        );
+    }
+
+    #[test]
+    fn test_authority_serde_round_trip() {
+        let authority: Authority = serde_json::from_str("5").unwrap();
+        assert_eq!(authority.value(), 5);
+        assert_eq!(serde_json::to_string(&authority).unwrap(), "5");
+
+        let sub_authority: SubAuthority = serde_json::from_str("544").unwrap();
+        assert_eq!(sub_authority.value(), 544);
+        assert_eq!(serde_json::to_string(&sub_authority).unwrap(), "544");
     }
 
     #[test]
     fn test_parse_sub_authority() {
         let buffer: &[u8] = &[0x12, 0x00, 0x00, 0x00];
 
-        let sub_authority = SubAuthority::from_buffer(&buffer).unwrap();
+        let sub_authority = SubAuthority::from_buffer(buffer).unwrap();
         assert_eq!(sub_authority.0, 18);
+        assert_eq!(sub_authority.value(), 18);
     }
 
     #[test]
@@ -104,10 +205,44 @@ mod tests {
             0x12, 0x00, 0x00, 0x00, 0x00, 0x13, 0x18, 0x00, 0x3F, 0x00, 0x0F, 0x00,
         ];
 
-        let sub_authority = SubAuthorityList::from_buffer(&buffer, 3).unwrap();
+        let sub_authority = SubAuthorityList::from_buffer(buffer, 3).unwrap();
 
         assert_eq!(sub_authority.0[0].0, 18);
         assert_eq!(sub_authority.0[1].0, 1_577_728);
         assert_eq!(sub_authority.0[2].0, 983_103);
     }
+
+    #[test]
+    fn test_indexes_sub_authority_list() {
+        let buffer: &[u8] = &[
+            0x15, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0xE9, 0x03, 0x00, 0x00,
+        ];
+
+        let list = SubAuthorityList::from_buffer(buffer, 3).unwrap();
+
+        assert_eq!(list.len(), 3);
+        assert!(!list.is_empty());
+        assert_eq!(list[0].value(), 21);
+        assert_eq!(list[2].value(), 1001);
+        assert_eq!(list.get(1).map(SubAuthority::value), Some(1));
+        assert!(list.get(3).is_none());
+
+        let values: Vec<u32> = list.iter().map(SubAuthority::value).collect();
+        assert_eq!(values, vec![21, 1, 1001]);
+
+        assert!(SubAuthorityList::from_buffer(&[], 0).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_formats_sub_authority_list() {
+        let buffer: &[u8] = &[0x20, 0x00, 0x00, 0x00, 0x20, 0x02, 0x00, 0x00];
+
+        let list = SubAuthorityList::from_buffer(buffer, 2).unwrap();
+        assert_eq!(list.to_dashed_string(), "-32-544");
+        assert_eq!(list.to_string(), "-32-544");
+        assert_eq!(format!("S-1-5{}", list), "S-1-5-32-544");
+
+        let joined: Vec<String> = list.iter().map(SubAuthority::to_string).collect();
+        assert_eq!(joined.join("."), "32.544");
+    }
 }
@@ -3,12 +3,24 @@
 
 mod ace;
 mod acl;
+pub mod ad_rights;
 mod authority;
 mod sec_desc;
 mod sid;
+mod sid_ref;
+pub mod well_known;
 
-pub use self::ace::{Ace, AceBasic, AceData, AceObject, AceType};
-pub use self::acl::Acl;
+pub use self::ace::{
+    parse_sid_at_end, AccessContext, Ace, AceBasic, AceCompound, AceData, AceDataKind, AceObject,
+    AceObjectFlags, AceType, ProcessTrustLabel, RawAce,
+};
+pub use self::acl::{AceIter, Acl};
+pub use self::ad_rights::AdObjectType;
 pub use self::authority::{Authority, SubAuthority, SubAuthorityList};
-pub use self::sec_desc::{SecDescHeader, SecurityDescriptor};
-pub use self::sid::Sid;
+pub use self::sec_desc::{
+    DaclSemantics, SdControlFlags, SecDescHeader, SecurityDescriptor, SecurityDescriptorIter,
+    SecurityDescriptorParser, Warning,
+};
+pub use self::sid::{Sid, SidKind, SidPattern};
+pub use self::sid_ref::SidRef;
+pub use self::well_known::WellKnownSid;
@@ -1,17 +1,33 @@
-use crate::err::Result;
-use crate::security::acl::Acl;
+use crate::err::{Error, Result};
+use crate::macros::EMPTY_FLAGS;
+use crate::security::ace::{access_rights_names, AccessContext, Ace, AceData, AceType};
+use crate::security::acl::{AceIter, Acl};
 use crate::security::sid::Sid;
+use crate::utils;
 use crate::ReadSeek;
-use bitflags::bitflags;
-use byteorder::{LittleEndian, ReadBytesExt};
+use bitflags::parser::WriteHex;
+use bitflags::{bitflags, Flags};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
 
-use serde::Serialize;
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Serialize, Serializer};
 
-use std::io::{Cursor, Read, SeekFrom};
+use std::fmt::Write as _;
+use std::hash::{Hash, Hasher};
+use std::io::{Cursor, Read, SeekFrom, Write};
+#[cfg(feature = "tokio")]
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncSeek, AsyncSeekExt};
 
-#[derive(Serialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde-camelcase", serde(rename_all = "camelCase"))]
+#[serde(from = "DeserializedSecurityDescriptor")]
 pub struct SecurityDescriptor {
-    #[serde(skip_serializing)]
+    /// Only the revision, the control flags and the RM control byte of the header are serialized,
+    /// since the offsets only describe the layout. When deserializing input without a header,
+    /// one is made with `SE_DACL_PRESENT` and `SE_SACL_PRESENT` set for the ACLs which are there.
+    #[serde(default)]
     pub header: SecDescHeader,
     pub owner_sid: Sid,
     pub group_sid: Sid,
@@ -19,29 +35,711 @@ pub struct SecurityDescriptor {
     pub sacl: Option<Acl>,
 }
 
+/// A `SecurityDescriptor` as deserialized, which may lack its header.
+#[derive(Deserialize)]
+#[cfg_attr(feature = "serde-camelcase", serde(rename_all = "camelCase"))]
+struct DeserializedSecurityDescriptor {
+    #[serde(default)]
+    header: Option<SecDescHeader>,
+    owner_sid: Sid,
+    group_sid: Sid,
+    dacl: Option<Acl>,
+    sacl: Option<Acl>,
+}
+
+impl From<DeserializedSecurityDescriptor> for SecurityDescriptor {
+    fn from(sd: DeserializedSecurityDescriptor) -> Self {
+        let DeserializedSecurityDescriptor {
+            header,
+            owner_sid,
+            group_sid,
+            dacl,
+            sacl,
+        } = sd;
+
+        // A default header would mark the DACL as absent, which grants everyone full access.
+        let header = header.unwrap_or_else(|| {
+            let mut control_flags = SdControlFlags::SE_SELF_RELATIVE;
+            control_flags.set(SdControlFlags::SE_DACL_PRESENT, dacl.is_some());
+            control_flags.set(SdControlFlags::SE_SACL_PRESENT, sacl.is_some());
+
+            SecDescHeader::new(control_flags)
+        });
+
+        SecurityDescriptor {
+            header,
+            owner_sid,
+            group_sid,
+            dacl,
+            sacl,
+        }
+    }
+}
+
+/// Equality ignores the offsets in the header, which only describe the layout.
+impl PartialEq for SecurityDescriptor {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for SecurityDescriptor {}
+
+impl Hash for SecurityDescriptor {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key().hash(state)
+    }
+}
+
 impl SecurityDescriptor {
+    fn key(
+        &self,
+    ) -> (
+        u8,
+        u8,
+        SdControlFlags,
+        &Sid,
+        &Sid,
+        &Option<Acl>,
+        &Option<Acl>,
+    ) {
+        (
+            self.header.revision_number,
+            self.header.rm_control,
+            self.header.control_flags,
+            &self.owner_sid,
+            &self.group_sid,
+            &self.dacl,
+            &self.sacl,
+        )
+    }
+
+    /// Since the length of the descriptor is known, the header offsets are validated before parsing.
+    pub fn from_buffer(buffer: &[u8]) -> Result<Self> {
+        SecurityDescriptorParser::new()
+            .validate_offsets(true)
+            .parse_buffer(buffer)
+    }
+
     pub fn from_stream<S: ReadSeek>(stream: &mut S) -> Result<SecurityDescriptor> {
+        SecurityDescriptorParser::new().parse_stream(stream)
+    }
+
+    /// Parses as much of a (possibly damaged) descriptor as possible, collecting a `Warning`
+    /// for every problem the strict parser would have stopped at.
+    ///
+    /// Only a truncated header is an error. A SID which can't be parsed is replaced by `S-1-0`,
+    /// and an ACL which can't be parsed by `None`. ACEs of an unknown type are kept as `AceType::Unknown`.
+    pub fn from_buffer_lenient(buffer: &[u8]) -> Result<(SecurityDescriptor, Vec<Warning>)> {
+        let header = SecDescHeader::from_buffer(buffer)?;
+        let mut warnings = Vec::new();
+
+        if header.revision_number != SecDescHeader::SECURITY_DESCRIPTOR_REVISION {
+            warnings.push(Warning::UnsupportedRevision {
+                structure: "security descriptor",
+                found: header.revision_number,
+            });
+        }
+
+        let owner_sid =
+            read_sid_lenient(buffer, "owner SID", header.owner_sid_offset, &mut warnings);
+        let group_sid =
+            read_sid_lenient(buffer, "group SID", header.group_sid_offset, &mut warnings);
+        let dacl = read_acl_lenient(buffer, "DACL", header.dacl_offset, &mut warnings);
+        let sacl = read_acl_lenient(buffer, "SACL", header.sacl_offset, &mut warnings);
+
+        let sd = SecurityDescriptor {
+            header,
+            owner_sid,
+            group_sid,
+            dacl,
+            sacl,
+        };
+
+        Ok((sd, warnings))
+    }
+
+    /// Lazily parses `count` back to back descriptors, starting at the current position.
+    /// Each descriptor is assumed to end right after the last of its parts.
+    pub fn iter_from_stream<S: ReadSeek>(
+        stream: &mut S,
+        count: usize,
+    ) -> SecurityDescriptorIter<'_, S> {
+        SecurityDescriptorIter {
+            stream,
+            remaining: count,
+        }
+    }
+
+    /// The offset of the end of the last part of the descriptor, relative to its start.
+    fn extent(&self) -> u64 {
+        let header = &self.header;
+        let parts = [
+            (header.owner_sid_offset, self.owner_sid.byte_len()),
+            (header.group_sid_offset, self.group_sid.byte_len()),
+            (
+                header.dacl_offset,
+                self.dacl.as_ref().map_or(0, Acl::byte_len),
+            ),
+            (
+                header.sacl_offset,
+                self.sacl.as_ref().map_or(0, Acl::byte_len),
+            ),
+        ];
+
+        parts
+            .iter()
+            .map(|(offset, len)| u64::from(*offset) + *len as u64)
+            .fold(SecDescHeader::SIZE as u64, std::cmp::max)
+    }
+
+    /// Parses the descriptor stored in a registry hive key security (`sk`) cell.
+    ///
+    /// These are written by many different versions of Windows, so ACEs of types which aren't known
+    /// to this crate are kept as `AceData::Unhandled` rather than failing the whole descriptor.
+    pub fn from_registry_hive_bytes(buffer: &[u8]) -> Result<Self> {
+        SecurityDescriptorParser::new()
+            .tolerate_unknown_ace_types(true)
+            .validate_offsets(true)
+            .parse_buffer(buffer)
+    }
+
+    /// The size of the descriptor in self-relative form, with the SIDs and ACLs
+    /// packed directly after the 20 byte header.
+    pub fn byte_len(&self) -> usize {
+        20 + self.owner_sid.byte_len()
+            + self.group_sid.byte_len()
+            + self.dacl.as_ref().map_or(0, Acl::byte_len)
+            + self.sacl.as_ref().map_or(0, Acl::byte_len)
+    }
+
+    /// `byte_len` rounded up to a multiple of `alignment`, which is where the next field starts
+    /// when the descriptor is embedded in a larger structure. An alignment of 0 is treated as 1.
+    pub fn aligned_byte_len(&self, alignment: usize) -> usize {
+        let alignment = std::cmp::max(alignment, 1);
+
+        self.byte_len().div_ceil(alignment) * alignment
+    }
+
+    pub fn is_dacl_present(&self) -> bool {
+        self.header
+            .control_flags
+            .contains(SdControlFlags::SE_DACL_PRESENT)
+    }
+
+    /// How the DACL controls access. Windows ignores the DACL when `SE_DACL_PRESENT` isn't set,
+    /// so that is treated like a NULL DACL.
+    pub fn dacl_semantics(&self) -> DaclSemantics {
+        match &self.dacl {
+            Some(_) if !self.is_dacl_present() => DaclSemantics::NullAllowAll,
+            Some(dacl) if dacl.entries.is_empty() => DaclSemantics::EmptyDenyAll,
+            Some(_) => DaclSemantics::Explicit,
+            None => DaclSemantics::NullAllowAll,
+        }
+    }
+
+    /// Whether the DACL is protected from inheriting ACEs from the parent.
+    pub fn is_dacl_protected(&self) -> bool {
+        self.header
+            .control_flags
+            .contains(SdControlFlags::SE_DACL_PROTECTED)
+    }
+
+    pub fn is_sacl_present(&self) -> bool {
+        self.header
+            .control_flags
+            .contains(SdControlFlags::SE_SACL_PRESENT)
+    }
+
+    /// Whether the descriptor is in self-relative form (offsets instead of pointers).
+    pub fn is_self_relative(&self) -> bool {
+        self.header
+            .control_flags
+            .contains(SdControlFlags::SE_SELF_RELATIVE)
+    }
+
+    /// Writes the descriptor in self-relative form: the header followed by the owner, the group,
+    /// the SACL and the DACL. The offsets in the header are computed from the contents,
+    /// and so are `SE_SELF_RELATIVE`, `SE_DACL_PRESENT` and `SE_SACL_PRESENT`.
+    /// The revision and the other flags are kept.
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> Result<()> {
+        let mut control_flags = self.header.control_flags | SdControlFlags::SE_SELF_RELATIVE;
+        control_flags.set(SdControlFlags::SE_DACL_PRESENT, self.dacl.is_some());
+        control_flags.set(SdControlFlags::SE_SACL_PRESENT, self.sacl.is_some());
+
+        let mut header = SecDescHeader {
+            revision_number: self.header.revision_number,
+            rm_control: self.header.rm_control,
+            ..SecDescHeader::new(control_flags)
+        };
+
+        // The whole descriptor is bounded by the sizes of two ACLs and two SIDs, so the offsets fit.
+        let mut offset = SecDescHeader::SIZE;
+        header.set_owner_sid_offset(offset as u32);
+        offset += self.owner_sid.byte_len();
+        header.set_group_sid_offset(offset as u32);
+        offset += self.group_sid.byte_len();
+
+        if let Some(sacl) = &self.sacl {
+            header.set_sacl_offset(offset as u32);
+            offset += sacl.byte_len();
+        }
+        if self.dacl.is_some() {
+            header.set_dacl_offset(offset as u32);
+        }
+
+        header.write_to(writer)?;
+        self.owner_sid.write_to(writer)?;
+        self.group_sid.write_to(writer)?;
+
+        for acl in [&self.sacl, &self.dacl].iter().copied().flatten() {
+            acl.write_to(writer)?;
+        }
+
+        Ok(())
+    }
+
+    /// Renders the descriptor as indented text for debugging: the owner and group, followed by
+    /// every ACE of the DACL and SACL with its flags, rights and SID. Well known SIDs are named,
+    /// and access rights are decoded as file rights since the kind of object isn't known.
+    ///
+    /// Unlike SDDL, this is meant for people and can't be parsed back.
+    pub fn to_human_string(&self) -> String {
+        let mut out = String::new();
+
+        // Writing to a String cannot fail.
+        let _ = writeln!(out, "Owner: {}", self.owner_sid.to_string_named());
+        let _ = writeln!(out, "Group: {}", self.group_sid.to_string_named());
+        let _ = writeln!(out, "Control: {}", flag_names(&self.header.control_flags));
+
+        for (name, acl) in [("DACL", &self.dacl), ("SACL", &self.sacl)].iter() {
+            match acl {
+                Some(acl) => {
+                    let _ = writeln!(out, "{} (revision {}):", name, acl.revision);
+
+                    for ace in &acl.entries {
+                        write_human_ace(&mut out, ace);
+                    }
+                }
+                None => {
+                    let _ = writeln!(out, "{}: not present", name);
+                }
+            }
+        }
+
+        out
+    }
+
+    #[cfg(feature = "tokio")]
+    pub async fn from_async_stream<S>(stream: &mut S) -> Result<SecurityDescriptor>
+    where
+        S: AsyncRead + AsyncSeek + Unpin,
+    {
+        SecurityDescriptorParser::new()
+            .parse_async_stream(stream)
+            .await
+    }
+}
+
+/// Writes one ACE of `SecurityDescriptor::to_human_string`, indented under its ACL.
+fn write_human_ace(out: &mut String, ace: &Ace) {
+    let rights = |mask| access_rights_names(mask, AccessContext::File).join(" | ");
+
+    let _ = writeln!(out, "  {:?}", ace.ace_type);
+    let _ = writeln!(out, "    Flags: {}", flag_names(&ace.ace_flags));
+
+    match &ace.data {
+        AceData::Basic(basic) => {
+            let _ = writeln!(out, "    Rights: {}", rights(basic.access_rights));
+            let _ = writeln!(out, "    SID: {}", basic.sid.to_string_named());
+        }
+        AceData::Object(object) => {
+            let _ = writeln!(out, "    Rights: {}", rights(object.access_rights));
+
+            if let Some(object_type) = &object.object_type {
+                let name = object.object_type_name().unwrap_or("unknown");
+                let _ = writeln!(out, "    Object type: {} ({})", object_type, name);
+            }
+            if let Some(inherited_type) = &object.inherited_type {
+                let _ = writeln!(out, "    Inherited object type: {}", inherited_type);
+            }
+
+            let _ = writeln!(out, "    SID: {}", object.sid.to_string_named());
+        }
+        AceData::Compound(compound) => {
+            let _ = writeln!(out, "    Rights: {}", rights(compound.access_rights));
+            let _ = writeln!(
+                out,
+                "    Server SID: {}",
+                compound.server_sid.to_string_named()
+            );
+            let _ = writeln!(
+                out,
+                "    Client SID: {}",
+                compound.client_sid.to_string_named()
+            );
+        }
+        AceData::Unhandled(raw) => {
+            let _ = writeln!(out, "    Data: {} bytes", raw.0.len());
+        }
+    }
+}
+
+/// Like the serialized form of the flags, `FLAG_A | FLAG_B`, or `NONE` when empty.
+fn flag_names<F: Flags>(flags: &F) -> String
+where
+    F::Bits: WriteHex,
+{
+    if flags.is_empty() {
+        return EMPTY_FLAGS.to_string();
+    }
+
+    let mut names = String::new();
+    bitflags::parser::to_writer(flags, &mut names).expect("Writing to a String cannot fail");
+
+    names
+}
+
+/// A problem found by `SecurityDescriptor::from_buffer_lenient`.
+/// These mirror the errors of the strict parser, with the part of the descriptor they were found in.
+#[derive(Debug, thiserror::Error)]
+pub enum Warning {
+    #[error("Unsupported {} revision: {}", structure, found)]
+    UnsupportedRevision { structure: &'static str, found: u8 },
+    #[error(
+        "{} offset {} is outside of the {} byte security descriptor",
+        field,
+        offset,
+        len
+    )]
+    OffsetOutOfBounds {
+        field: &'static str,
+        offset: u32,
+        len: u64,
+    },
+    #[error("Unknown AceType in {}: {}", field, ace_type)]
+    UnknownAceType { field: &'static str, ace_type: u8 },
+    #[error(
+        "ACE of type {} in {} is not allowed in an ACL of revision {}",
+        ace_type,
+        field,
+        revision
+    )]
+    AceIncompatibleWithAclRevision {
+        field: &'static str,
+        ace_type: u8,
+        revision: u8,
+    },
+    #[error(
+        "{} declares a size of {} bytes, but its ACEs take up {}",
+        field,
+        declared,
+        actual
+    )]
+    AclSizeMismatch {
+        field: &'static str,
+        declared: u16,
+        actual: usize,
+    },
+    /// The part could not be parsed at all, and was replaced.
+    #[error("Failed to parse {}: {}", field, error)]
+    Unparsed { field: &'static str, error: Error },
+}
+
+/// The part of `buffer` starting at `offset`, or `None` (with a warning) if it is out of bounds.
+fn part_lenient<'a>(
+    buffer: &'a [u8],
+    field: &'static str,
+    offset: u32,
+    warnings: &mut Vec<Warning>,
+) -> Option<&'a [u8]> {
+    match buffer.get(offset as usize..) {
+        Some(part) if !part.is_empty() => Some(part),
+        _ => {
+            warnings.push(Warning::OffsetOutOfBounds {
+                field,
+                offset,
+                len: buffer.len() as u64,
+            });
+            None
+        }
+    }
+}
+
+fn read_sid_lenient(
+    buffer: &[u8],
+    field: &'static str,
+    offset: u32,
+    warnings: &mut Vec<Warning>,
+) -> Sid {
+    let null_sid = || Sid::new(Sid::SID_REVISION, 0, &[]).expect("S-1-0 is a valid SID");
+
+    let part = match part_lenient(buffer, field, offset, warnings) {
+        Some(part) => part,
+        None => return null_sid(),
+    };
+
+    if part[0] != Sid::SID_REVISION {
+        warnings.push(Warning::UnsupportedRevision {
+            structure: "SID",
+            found: part[0],
+        });
+    }
+
+    Sid::from_buffer(part).unwrap_or_else(|error| {
+        warnings.push(Warning::Unparsed { field, error });
+        null_sid()
+    })
+}
+
+fn read_acl_lenient(
+    buffer: &[u8],
+    field: &'static str,
+    offset: u32,
+    warnings: &mut Vec<Warning>,
+) -> Option<Acl> {
+    if offset == 0 {
+        return None;
+    }
+
+    let part = part_lenient(buffer, field, offset, warnings)?;
+    let parser = SecurityDescriptorParser::new().tolerate_unknown_ace_types(true);
+
+    let acl = match Acl::read(&mut Cursor::new(part), &parser) {
+        Ok(acl) => acl,
+        Err(error) => {
+            warnings.push(Warning::Unparsed { field, error });
+            return None;
+        }
+    };
+
+    if acl.revision != Acl::ACL_REVISION && acl.revision != Acl::ACL_REVISION_DS {
+        warnings.push(Warning::UnsupportedRevision {
+            structure: "ACL",
+            found: acl.revision,
+        });
+    }
+
+    for ace in &acl.entries {
+        if let AceType::Unknown(ace_type) = ace.ace_type {
+            warnings.push(Warning::UnknownAceType { field, ace_type });
+        } else if ace.ace_type.is_object() && acl.revision != Acl::ACL_REVISION_DS {
+            warnings.push(Warning::AceIncompatibleWithAclRevision {
+                field,
+                ace_type: ace.ace_type.as_u8(),
+                revision: acl.revision,
+            });
+        }
+    }
+
+    if let Err(Error::AclSizeMismatch { declared, actual }) = acl.validate() {
+        warnings.push(Warning::AclSizeMismatch {
+            field,
+            declared,
+            actual,
+        });
+    }
+
+    Some(acl)
+}
+
+/// The difference between a missing DACL and an empty one, see `SecurityDescriptor::dacl_semantics`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DaclSemantics {
+    /// There is no DACL (a NULL DACL), so everyone is allowed full access.
+    NullAllowAll,
+    /// The DACL has no entries, so everyone is denied access.
+    EmptyDenyAll,
+    /// Access is determined by the entries of the DACL.
+    Explicit,
+}
+
+/// Yields back to back descriptors, see `SecurityDescriptor::iter_from_stream`.
+/// Iteration stops after the first error.
+pub struct SecurityDescriptorIter<'a, S: ReadSeek> {
+    stream: &'a mut S,
+    remaining: usize,
+}
+
+impl<'a, S: ReadSeek> Iterator for SecurityDescriptorIter<'a, S> {
+    type Item = Result<SecurityDescriptor>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        let read = |stream: &mut S| {
+            let start_offset = stream.tell()?;
+            let sd = SecurityDescriptor::from_stream(stream)?;
+            stream.seek(SeekFrom::Start(start_offset + sd.extent()))?;
+
+            Ok(sd)
+        };
+
+        let sd = read(self.stream);
+
+        self.remaining = if sd.is_ok() { self.remaining - 1 } else { 0 };
+
+        Some(sd)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.remaining))
+    }
+}
+
+/// Configures how strictly a `SecurityDescriptor` is parsed.
+///
+/// By default nothing beyond the structure itself is validated, and unknown ACE types are an error,
+/// which matches `SecurityDescriptor::from_stream`.
+///
+/// ```
+/// # use winstructs::security::SecurityDescriptorParser;
+/// # use std::io::Cursor;
+/// # let buffer: &[u8] = &[
+/// #     0x01, 0x00, 0x00, 0x80, 0x14, 0x00, 0x00, 0x00, 0x20, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+/// #     0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x05, 0x12, 0x00,
+/// #     0x00, 0x00, 0x01, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x05, 0x12, 0x00, 0x00, 0x00,
+/// # ];
+/// let sd = SecurityDescriptorParser::new()
+///     .tolerate_unknown_ace_types(true)
+///     .validate_revisions(true)
+///     .validate_offsets(true)
+///     .parse_stream(&mut Cursor::new(buffer))
+///     .unwrap();
+///
+/// assert_eq!(sd.owner_sid.to_string(), "S-1-5-18");
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SecurityDescriptorParser {
+    pub(crate) tolerate_unknown_ace_types: bool,
+    pub(crate) validate_revisions: bool,
+    validate_offsets: bool,
+}
+
+impl SecurityDescriptorParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keep ACEs of an unknown type as `AceType::Unknown` with an unparsed body, instead of erroring.
+    pub fn tolerate_unknown_ace_types(mut self, tolerate: bool) -> Self {
+        self.tolerate_unknown_ace_types = tolerate;
+        self
+    }
+
+    /// Error on unknown revisions of the header, the SIDs and the ACLs,
+    /// and on object ACEs in ACLs whose revision doesn't allow them.
+    pub fn validate_revisions(mut self, validate: bool) -> Self {
+        self.validate_revisions = validate;
+        self
+    }
+
+    /// Error on header offsets which point past the end of the descriptor.
+    /// For streams, the descriptor is assumed to extend to the end of the stream.
+    pub fn validate_offsets(mut self, validate: bool) -> Self {
+        self.validate_offsets = validate;
+        self
+    }
+
+    pub fn parse_buffer(&self, buffer: &[u8]) -> Result<SecurityDescriptor> {
+        self.parse_stream(&mut Cursor::new(buffer))
+    }
+
+    /// Reads an ACL header, and returns an iterator which lazily reads its ACEs with these options.
+    pub fn iter_acl<'a, R: Read>(&self, reader: &'a mut R) -> Result<AceIter<'a, R>> {
+        Acl::iter_reader_with(reader, self)
+    }
+
+    /// Like `parse_stream`, but for async streams. Only the sizes of the parts are read at first,
+    /// and then the whole descriptor is read into memory and parsed with `parse_buffer`.
+    #[cfg(feature = "tokio")]
+    pub async fn parse_async_stream<S>(&self, stream: &mut S) -> Result<SecurityDescriptor>
+    where
+        S: AsyncRead + AsyncSeek + Unpin,
+    {
+        let start_offset = stream.stream_position().await?;
+
+        let mut header_buffer = [0; SecDescHeader::SIZE];
+        stream.read_exact(&mut header_buffer).await?;
+        let header = SecDescHeader::from_buffer(&header_buffer)?;
+
+        // Like `parse_stream`, the SIDs are always read, while ACLs at offset 0 are absent.
+        let parts = [
+            (header.owner_sid_offset, false),
+            (header.group_sid_offset, false),
+            (header.sacl_offset, true),
+            (header.dacl_offset, true),
+        ];
+
+        let mut len = SecDescHeader::SIZE as u64;
+
+        for (offset, is_acl) in parts.iter() {
+            if *is_acl && *offset == 0 {
+                continue;
+            }
+
+            stream
+                .seek(SeekFrom::Start(start_offset + u64::from(*offset)))
+                .await?;
+
+            // The size of a SID follows from its sub authority count, the second byte,
+            // and the size of an ACL is at offset 2.
+            let mut prefix = [0; 4];
+            stream.read_exact(&mut prefix).await?;
+
+            let part_len = if *is_acl {
+                std::cmp::max(u64::from(u16::from_le_bytes([prefix[2], prefix[3]])), 8)
+            } else {
+                8 + 4 * u64::from(prefix[1])
+            };
+
+            len = std::cmp::max(len, u64::from(*offset) + part_len);
+        }
+
+        let mut buffer = vec![0; len as usize];
+        stream.seek(SeekFrom::Start(start_offset)).await?;
+        stream.read_exact(&mut buffer).await?;
+
+        self.parse_buffer(&buffer)
+    }
+
+    pub fn parse_stream<S: ReadSeek>(&self, stream: &mut S) -> Result<SecurityDescriptor> {
         let start_offset = stream.tell()?;
 
-        let header = SecDescHeader::from_reader(stream)?;
+        let header = SecDescHeader::read(stream, self.validate_revisions)?;
+
+        if self.validate_offsets {
+            let end_offset = stream.seek(SeekFrom::End(0))?;
+            header.validate_offsets(end_offset.saturating_sub(start_offset))?;
+        }
+
+        let read_sid = |stream: &mut S| {
+            if self.validate_revisions {
+                Sid::from_reader_validated(stream)
+            } else {
+                Sid::from_reader(stream)
+            }
+        };
 
         stream.seek(SeekFrom::Start(
             start_offset + u64::from(header.owner_sid_offset),
         ))?;
 
-        let owner_sid = Sid::from_reader(stream)?;
+        let owner_sid = read_sid(stream)?;
 
         stream.seek(SeekFrom::Start(
             start_offset + u64::from(header.group_sid_offset),
         ))?;
 
-        let group_sid = Sid::from_reader(stream)?;
+        let group_sid = read_sid(stream)?;
 
         let dacl = if header.dacl_offset > 0 {
             stream.seek(SeekFrom::Start(
                 start_offset + u64::from(header.dacl_offset),
             ))?;
-            Some(Acl::from_reader(stream)?)
+            Some(Acl::read(stream, self)?)
         } else {
             None
         };
@@ -50,7 +748,7 @@ impl SecurityDescriptor {
             stream.seek(SeekFrom::Start(
                 start_offset + u64::from(header.sacl_offset),
             ))?;
-            Some(Acl::from_reader(stream)?)
+            Some(Acl::read(stream, self)?)
         } else {
             None
         };
@@ -68,6 +766,7 @@ impl SecurityDescriptor {
 // Security Descriptor Header
 // https://github.com/libyal/libfwnt/wiki/Security-Descriptor
 bitflags! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
     pub struct SdControlFlags: u16 {
         const SE_OWNER_DEFAULTED             = 0x0001;
         const SE_GROUP_DEFAULTED             = 0x0002;
@@ -79,6 +778,7 @@ bitflags! {
         const SE_SACL_AUTO_INHERIT_REQ       = 0x0200;
         const SE_DACL_AUTO_INHERITED         = 0x0400;
         const SE_SACL_AUTO_INHERITED         = 0x0800;
+        const SE_DACL_PROTECTED              = 0x1000;
         const SE_SACL_PROTECTED              = 0x2000;
         const SE_RM_CONTROL_VALID            = 0x4000;
         const SE_SELF_RELATIVE               = 0x8000;
@@ -86,33 +786,149 @@ bitflags! {
 }
 
 impl_serialize_for_bitflags! {SdControlFlags}
+impl_deserialize_for_bitflags! {SdControlFlags}
+impl_arbitrary_for_bitflags! {SdControlFlags}
+impl_json_schema_as_string! {SdControlFlags}
 
-#[derive(Serialize, Debug, Clone)]
+/// Only the revision, the control flags and (when valid) the RM control byte are serialized.
+#[derive(Deserialize, Debug, Clone, Default)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde-camelcase", serde(rename_all = "camelCase"))]
 pub struct SecDescHeader {
     pub revision_number: u8,
-    #[serde(skip_serializing)]
-    pub padding1: u8,
+    /// The resource manager control bits (`Sbz1`), which are only meaningful
+    /// when `SE_RM_CONTROL_VALID` is set.
+    #[serde(default)]
+    pub rm_control: u8,
+    /// Bits which are not known to this crate are retained as is.
     pub control_flags: SdControlFlags,
-    #[serde(skip_serializing)]
+    #[serde(default)]
+    #[cfg_attr(feature = "schemars", schemars(skip))]
     pub owner_sid_offset: u32,
-    #[serde(skip_serializing)]
+    #[serde(default)]
+    #[cfg_attr(feature = "schemars", schemars(skip))]
     pub group_sid_offset: u32,
-    #[serde(skip_serializing)]
+    #[serde(default)]
+    #[cfg_attr(feature = "schemars", schemars(skip))]
     pub sacl_offset: u32,
-    #[serde(skip_serializing)]
+    #[serde(default)]
+    #[cfg_attr(feature = "schemars", schemars(skip))]
     pub dacl_offset: u32,
 }
 
+// The names of the serialized header fields, which must match the `Deserialize` implementation.
+#[cfg(not(feature = "serde-camelcase"))]
+const HEADER_FIELDS: [&str; 3] = ["revision_number", "rm_control", "control_flags"];
+#[cfg(feature = "serde-camelcase")]
+const HEADER_FIELDS: [&str; 3] = ["revisionNumber", "rmControl", "controlFlags"];
+
+impl Serialize for SecDescHeader {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let [revision_number, rm_control, control_flags] = HEADER_FIELDS;
+        let rm_control_valid = self.is_rm_control_valid();
+
+        let mut state =
+            serializer.serialize_struct("SecDescHeader", 2 + usize::from(rm_control_valid))?;
+        state.serialize_field(revision_number, &self.revision_number)?;
+        if rm_control_valid {
+            state.serialize_field(rm_control, &self.rm_control)?;
+        } else {
+            state.skip_field(rm_control)?;
+        }
+        state.serialize_field(control_flags, &self.control_flags)?;
+        state.end()
+    }
+}
+
 impl SecDescHeader {
+    pub const SECURITY_DESCRIPTOR_REVISION: u8 = 1;
+    /// The size of the header in bytes.
+    pub const SIZE: usize = 20;
+
+    /// Creates a header of the current revision with `control_flags`, and all offsets set to 0 (absent).
+    pub fn new(control_flags: SdControlFlags) -> Self {
+        SecDescHeader {
+            revision_number: SecDescHeader::SECURITY_DESCRIPTOR_REVISION,
+            control_flags,
+            ..Default::default()
+        }
+    }
+
+    pub fn set_owner_sid_offset(&mut self, offset: u32) {
+        self.owner_sid_offset = offset;
+    }
+
+    pub fn set_group_sid_offset(&mut self, offset: u32) {
+        self.group_sid_offset = offset;
+    }
+
+    pub fn set_sacl_offset(&mut self, offset: u32) {
+        self.sacl_offset = offset;
+    }
+
+    pub fn set_dacl_offset(&mut self, offset: u32) {
+        self.dacl_offset = offset;
+    }
+
     pub fn from_buffer(buffer: &[u8]) -> Result<Self> {
         Self::from_reader(&mut Cursor::new(buffer))
     }
 
+    /// Writes the header in its binary form, the inverse of `from_reader`.
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_u8(self.revision_number)?;
+        writer.write_u8(self.rm_control)?;
+        writer.write_u16::<LittleEndian>(self.control_flags.bits())?;
+        writer.write_u32::<LittleEndian>(self.owner_sid_offset)?;
+        writer.write_u32::<LittleEndian>(self.group_sid_offset)?;
+        writer.write_u32::<LittleEndian>(self.sacl_offset)?;
+        writer.write_u32::<LittleEndian>(self.dacl_offset)?;
+
+        Ok(())
+    }
+
     pub fn from_reader<R: Read>(reader: &mut R) -> Result<SecDescHeader> {
+        Self::read(reader, false)
+    }
+
+    /// Like `from_reader`, but errors if the security descriptor revision is not a known one.
+    pub fn from_reader_validated<R: Read>(reader: &mut R) -> Result<SecDescHeader> {
+        Self::read(reader, true)
+    }
+
+    fn read<R: Read>(reader: &mut R, validate: bool) -> Result<SecDescHeader> {
+        // Read the whole header up front, so a short stream is reported as such
+        // rather than as an EOF somewhere in the middle of it.
+        let mut header = [0; SecDescHeader::SIZE];
+        let got = utils::read_up_to(reader, &mut header)?;
+
+        if got < SecDescHeader::SIZE {
+            return Err(Error::TruncatedHeader {
+                expected: SecDescHeader::SIZE,
+                got,
+            });
+        }
+
+        Self::parse(&mut Cursor::new(&header[..]), validate)
+    }
+
+    fn parse<R: Read>(reader: &mut R, validate: bool) -> Result<SecDescHeader> {
         let revision_number = reader.read_u8()?;
-        let padding1 = reader.read_u8()?;
+
+        if validate && revision_number != SecDescHeader::SECURITY_DESCRIPTOR_REVISION {
+            return Err(Error::UnsupportedRevision {
+                structure: "security descriptor",
+                found: revision_number,
+            });
+        }
+
+        let rm_control = reader.read_u8()?;
         let control_flags_bytes = reader.read_u16::<LittleEndian>()?;
-        let control_flags = SdControlFlags::from_bits_truncate(control_flags_bytes);
+        let control_flags = SdControlFlags::from_bits_retain(control_flags_bytes);
         let owner_sid_offset = reader.read_u32::<LittleEndian>()?;
         let group_sid_offset = reader.read_u32::<LittleEndian>()?;
 
@@ -125,7 +941,7 @@ impl SecDescHeader {
 
         Ok(SecDescHeader {
             revision_number,
-            padding1,
+            rm_control,
             control_flags,
             owner_sid_offset,
             group_sid_offset,
@@ -133,11 +949,615 @@ impl SecDescHeader {
             dacl_offset,
         })
     }
+
+    /// Checks that every non-zero offset falls within a descriptor of `total_len` bytes.
+    pub fn validate_offsets(&self, total_len: u64) -> Result<()> {
+        let offsets = [
+            ("owner SID", self.owner_sid_offset),
+            ("group SID", self.group_sid_offset),
+            ("SACL", self.sacl_offset),
+            ("DACL", self.dacl_offset),
+        ];
+
+        for (field, offset) in offsets.iter() {
+            if *offset != 0 && u64::from(*offset) >= total_len {
+                return Err(Error::OffsetOutOfBounds {
+                    field,
+                    offset: *offset,
+                    len: total_len,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn is_rm_control_valid(&self) -> bool {
+        self.control_flags
+            .contains(SdControlFlags::SE_RM_CONTROL_VALID)
+    }
+
+    /// Control flag bits which don't correspond to any known flag.
+    pub fn unknown_control_flags(&self) -> u16 {
+        self.control_flags.bits() & !SdControlFlags::all().bits()
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::security::sec_desc::SecDescHeader;
+    use crate::err::Error;
+    use crate::security::ace::{AceData, AceType};
+    use crate::security::sec_desc::{
+        DaclSemantics, SdControlFlags, SecDescHeader, SecurityDescriptor, SecurityDescriptorParser,
+        Warning,
+    };
+    use proptest::prelude::*;
+    use std::io::Cursor;
+
+    fn key(snake_case: &'static str, camel_case: &'static str) -> &'static str {
+        if cfg!(feature = "serde-camelcase") {
+            camel_case
+        } else {
+            snake_case
+        }
+    }
+
+    // Owner: S-1-5-32-544, Group: S-1-5-18, DACL: [ALLOW S-1-1-0 0x1F01FF]
+    const SECURITY_DESCRIPTOR: &[u8] = &[
+        0x01, 0x00, 0x04, 0x80, 0x14, 0x00, 0x00, 0x00, 0x24, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x30, 0x00, 0x00, 0x00, 0x01, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x05, 0x20, 0x00,
+        0x00, 0x00, 0x20, 0x02, 0x00, 0x00, 0x01, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x05, 0x12,
+        0x00, 0x00, 0x00, 0x02, 0x00, 0x1C, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x14, 0x00,
+        0xFF, 0x01, 0x1F, 0x00, 0x01, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00,
+        0x00,
+    ];
+
+    // Owner: S-1-5-32-544, Group: S-1-5-18, DACL: [ALLOW S-1-1-0 0x1F01FF]
+    // SACL: [MANDATORY_LABEL S-1-16-4096, RESOURCE_ATTRIBUTE S-1-1-0 + claim,
+    //        PROCESS_TRUST_LABEL S-1-19-512-8192, unknown type 0x20]
+    const REGISTRY_SECURITY_DESCRIPTOR: &[u8] = &[
+        0x01, 0x00, 0x14, 0x80, 0x88, 0x00, 0x00, 0x00, 0x98, 0x00, 0x00, 0x00, 0x14, 0x00, 0x00,
+        0x00, 0x6C, 0x00, 0x00, 0x00, 0x02, 0x00, 0x58, 0x00, 0x04, 0x00, 0x00, 0x00, 0x11, 0x00,
+        0x14, 0x00, 0x01, 0x00, 0x00, 0x00, 0x01, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x10, 0x00,
+        0x10, 0x00, 0x00, 0x12, 0x00, 0x1C, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x01, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x10, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00,
+        0x00, 0x14, 0x00, 0x18, 0x00, 0x03, 0x02, 0x02, 0x00, 0x01, 0x02, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x13, 0x00, 0x02, 0x00, 0x00, 0x00, 0x20, 0x00, 0x00, 0x20, 0x00, 0x08, 0x00, 0xDE,
+        0xAD, 0xBE, 0xEF, 0x02, 0x00, 0x1C, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x14, 0x00,
+        0xFF, 0x01, 0x1F, 0x00, 0x01, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00,
+        0x00, 0x01, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x05, 0x20, 0x00, 0x00, 0x00, 0x20, 0x02,
+        0x00, 0x00, 0x01, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x05, 0x12, 0x00, 0x00, 0x00,
+    ];
+
+    #[test]
+    fn test_parses_registry_security_descriptor() {
+        // The strict parser rejects the unknown ACE.
+        assert!(matches!(
+            SecurityDescriptor::from_buffer(REGISTRY_SECURITY_DESCRIPTOR),
+            Err(Error::UnknownAceType { ace_type: 0x20 })
+        ));
+
+        let sd =
+            SecurityDescriptor::from_registry_hive_bytes(REGISTRY_SECURITY_DESCRIPTOR).unwrap();
+
+        // The SIDs come after the ACLs, and end the descriptor.
+        assert_eq!(sd.extent(), REGISTRY_SECURITY_DESCRIPTOR.len() as u64);
+
+        assert_eq!(sd.owner_sid.to_string(), "S-1-5-32-544");
+        assert_eq!(sd.group_sid.to_string(), "S-1-5-18");
+        assert_eq!(sd.dacl.unwrap().entries.len(), 1);
+
+        let sacl = sd.sacl.unwrap();
+        let types: Vec<AceType> = sacl.entries.iter().map(|ace| ace.ace_type).collect();
+        assert_eq!(
+            types,
+            vec![
+                AceType::SystemMandatoryLabel,
+                AceType::SystemResourceAttribute,
+                AceType::SystemProcessTrustLabel,
+                AceType::Unknown(0x20),
+            ]
+        );
+
+        // Resource attribute (18) and process trust label (20) ACEs are parsed like basic ACEs,
+        // with the claim of the resource attribute kept after the SID.
+        assert!(matches!(&sacl.entries[1].data, AceData::Basic(basic) if basic.sid == "S-1-1-0"));
+        assert!(
+            matches!(&sacl.entries[1].data, AceData::Basic(basic) if basic.application_data.as_ref().unwrap().0.len() == 8)
+        );
+        assert!(sacl.entries[1].trailing.is_none());
+        assert!(
+            matches!(&sacl.entries[2].data, AceData::Basic(basic) if basic.sid == "S-1-19-512-8192")
+        );
+        assert!(
+            matches!(&sacl.entries[3].data, AceData::Unhandled(raw) if raw.0 == [0xDE, 0xAD, 0xBE, 0xEF])
+        );
+    }
+
+    #[test]
+    fn test_parses_security_descriptor() {
+        let sd = SecurityDescriptor::from_stream(&mut Cursor::new(SECURITY_DESCRIPTOR)).unwrap();
+
+        assert_eq!(sd.owner_sid.to_string(), "S-1-5-32-544");
+        assert_eq!(sd.group_sid.to_string(), "S-1-5-18");
+        assert!(sd.sacl.is_none());
+
+        assert_eq!(sd.byte_len(), SECURITY_DESCRIPTOR.len());
+
+        let dacl = sd.dacl.unwrap();
+        assert_eq!(dacl.entries.len(), 1);
+    }
+
+    #[test]
+    fn test_iterates_concatenated_descriptors() {
+        let mut buffer = SECURITY_DESCRIPTOR.to_vec();
+        buffer.extend_from_slice(SECURITY_DESCRIPTOR);
+        // Make the owner of the second one S-1-5-32-545.
+        buffer[SECURITY_DESCRIPTOR.len() + 0x20] = 0x21;
+        // The start of a third, truncated descriptor.
+        buffer.extend_from_slice(&[0x01, 0x00, 0x04, 0x80]);
+
+        let mut stream = Cursor::new(&buffer);
+        let owners: Vec<String> = SecurityDescriptor::iter_from_stream(&mut stream, 2)
+            .map(|sd| sd.unwrap().owner_sid.to_string())
+            .collect();
+
+        assert_eq!(owners, vec!["S-1-5-32-544", "S-1-5-32-545"]);
+        assert_eq!(stream.position(), 2 * SECURITY_DESCRIPTOR.len() as u64);
+
+        stream.set_position(0);
+        let results: Vec<_> = SecurityDescriptor::iter_from_stream(&mut stream, 5).collect();
+        assert_eq!(results.len(), 3);
+        assert!(results[2].is_err());
+    }
+
+    #[test]
+    fn test_aligned_byte_len() {
+        let mut sd = SecurityDescriptor::from_buffer(SECURITY_DESCRIPTOR).unwrap();
+        assert_eq!(sd.aligned_byte_len(4), 76);
+
+        // An odd amount of slack at the end of the DACL.
+        sd.dacl.as_mut().unwrap().slack = vec![0; 3];
+        assert_eq!(sd.byte_len(), 79);
+
+        assert_eq!(sd.aligned_byte_len(4), 80);
+        assert_eq!(sd.aligned_byte_len(8), 80);
+        assert_eq!(sd.aligned_byte_len(16), 80);
+        assert_eq!(sd.aligned_byte_len(32), 96);
+        assert_eq!(sd.aligned_byte_len(1), 79);
+        assert_eq!(sd.aligned_byte_len(0), 79);
+    }
+
+    #[test]
+    fn test_security_descriptor_equality() {
+        use std::collections::HashSet;
+
+        let sd = SecurityDescriptor::from_buffer(SECURITY_DESCRIPTOR).unwrap();
+        assert_eq!(sd, sd.clone());
+
+        let mut modified = sd.clone();
+        if let AceData::Basic(basic) = &mut modified.dacl.as_mut().unwrap().entries[0].data {
+            basic.access_rights = 0x1200A9;
+        }
+        assert_ne!(sd, modified);
+
+        // Only the layout differs: the DACL is declared larger than needed, leaving slack.
+        let mut relaid = SECURITY_DESCRIPTOR.to_vec();
+        relaid[50] = 0x20;
+        relaid.extend_from_slice(&[0; 4]);
+        let relaid = SecurityDescriptor::from_buffer(&relaid).unwrap();
+        assert_eq!(sd, relaid);
+
+        let mut flags = SECURITY_DESCRIPTOR.to_vec();
+        flags[3] = 0x90;
+        assert_ne!(sd, SecurityDescriptor::from_buffer(&flags).unwrap());
+
+        let unique: HashSet<_> = vec![sd.clone(), relaid, modified].into_iter().collect();
+        assert_eq!(unique.len(), 2);
+    }
+
+    #[test]
+    fn test_control_flag_predicates() {
+        let sd = SecurityDescriptor::from_buffer(SECURITY_DESCRIPTOR).unwrap();
+
+        assert!(sd.is_dacl_present());
+        assert!(sd.is_self_relative());
+        assert!(!sd.is_dacl_protected());
+        assert!(!sd.is_sacl_present());
+
+        let mut buffer = SECURITY_DESCRIPTOR.to_vec();
+        // SE_SELF_RELATIVE | SE_DACL_PROTECTED | SE_DACL_PRESENT
+        buffer[2..4].copy_from_slice(&[0x04, 0x90]);
+
+        let sd = SecurityDescriptor::from_buffer(&buffer).unwrap();
+        assert!(sd.is_dacl_protected());
+        assert_eq!(sd.header.unknown_control_flags(), 0);
+    }
+
+    #[test]
+    fn test_dacl_semantics() {
+        let sd = SecurityDescriptor::from_buffer(SECURITY_DESCRIPTOR).unwrap();
+        assert_eq!(sd.dacl_semantics(), DaclSemantics::Explicit);
+
+        let mut empty = sd.clone();
+        empty.dacl.as_mut().unwrap().entries.clear();
+        assert_eq!(empty.dacl_semantics(), DaclSemantics::EmptyDenyAll);
+
+        let mut null = sd.clone();
+        null.dacl = None;
+        assert_eq!(null.dacl_semantics(), DaclSemantics::NullAllowAll);
+
+        let mut not_present = sd;
+        not_present
+            .header
+            .control_flags
+            .remove(SdControlFlags::SE_DACL_PRESENT);
+        assert_eq!(not_present.dacl_semantics(), DaclSemantics::NullAllowAll);
+    }
+
+    #[test]
+    fn test_dacl_semantics_after_deserializing() {
+        let sd = SecurityDescriptor::from_buffer(SECURITY_DESCRIPTOR).unwrap();
+        let mut json = serde_json::to_value(&sd).unwrap();
+
+        let deserialized: SecurityDescriptor = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(deserialized.dacl_semantics(), DaclSemantics::Explicit);
+
+        // Without a header, the DACL which is there must not be treated as absent.
+        json.as_object_mut().unwrap().remove("header");
+        let deserialized: SecurityDescriptor = serde_json::from_value(json).unwrap();
+        assert!(deserialized.is_dacl_present());
+        assert!(!deserialized.is_sacl_present());
+        assert_eq!(deserialized.dacl_semantics(), DaclSemantics::Explicit);
+    }
+
+    #[test]
+    fn test_security_descriptor_serde_round_trip() {
+        let sd = SecurityDescriptor::from_buffer(SECURITY_DESCRIPTOR).unwrap();
+        let json = serde_json::to_value(&sd).unwrap();
+
+        let deserialized: SecurityDescriptor = serde_json::from_value(json.clone()).unwrap();
+
+        assert_eq!(serde_json::to_value(&deserialized).unwrap(), json);
+        assert_eq!(deserialized, sd);
+    }
+
+    #[cfg(feature = "serde-camelcase")]
+    #[test]
+    fn test_serializes_camel_case() {
+        let sd = SecurityDescriptor::from_buffer(SECURITY_DESCRIPTOR).unwrap();
+        let json = serde_json::to_value(&sd).unwrap();
+
+        assert_eq!(json["ownerSid"], "S-1-5-32-544");
+        assert_eq!(json["groupSid"], "S-1-5-18");
+        assert!(json.get("owner_sid").is_none());
+        assert_eq!(json["dacl"]["entries"][0]["aceType"], "ACCESS_ALLOWED");
+        let access_rights = &json["dacl"]["entries"][0]["data"]["accessRights"];
+        if cfg!(feature = "serde-access-masks") {
+            assert_eq!(access_rights["raw"], 0x1F01FF);
+        } else {
+            assert_eq!(*access_rights, 0x1F01FF);
+        }
+
+        let header = serde_json::to_value(&sd.header).unwrap();
+        assert_eq!(header["controlFlags"], "SE_DACL_PRESENT | SE_SELF_RELATIVE");
+
+        let deserialized: SecurityDescriptor = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&deserialized).unwrap(), json);
+        assert_eq!(deserialized.dacl, sd.dacl);
+    }
+
+    #[test]
+    fn test_control_flags_serde_round_trip() {
+        let flags = SdControlFlags::SE_DACL_PRESENT | SdControlFlags::SE_SELF_RELATIVE;
+
+        let json = serde_json::to_string(&flags).unwrap();
+        assert_eq!(json, "\"SE_DACL_PRESENT | SE_SELF_RELATIVE\"");
+
+        let deserialized: SdControlFlags = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized, flags);
+
+        assert!(serde_json::from_str::<SdControlFlags>("\"SE_NOT_A_FLAG\"").is_err());
+    }
+
+    #[test]
+    fn test_validates_sec_desc_revision() {
+        let mut buffer = SECURITY_DESCRIPTOR[..20].to_vec();
+        buffer[0] = 0x55;
+
+        assert!(SecDescHeader::from_buffer(&buffer).is_ok());
+        assert!(matches!(
+            SecDescHeader::from_reader_validated(&mut Cursor::new(&buffer)),
+            Err(Error::UnsupportedRevision { found: 0x55, .. })
+        ));
+    }
+
+    #[test]
+    fn test_rejects_out_of_bounds_offsets() {
+        let mut buffer = SECURITY_DESCRIPTOR.to_vec();
+        // DACL offset
+        buffer[16..20].copy_from_slice(&0x1000_u32.to_le_bytes());
+
+        assert!(matches!(
+            SecurityDescriptor::from_buffer(&buffer),
+            Err(Error::OffsetOutOfBounds {
+                field: "DACL",
+                offset: 0x1000,
+                len: 76
+            })
+        ));
+    }
+
+    #[test]
+    fn test_rejects_truncated_header() {
+        assert!(matches!(
+            SecDescHeader::from_buffer(&SECURITY_DESCRIPTOR[..10]),
+            Err(Error::TruncatedHeader {
+                expected: 20,
+                got: 10
+            })
+        ));
+    }
+
+    #[test]
+    fn test_serializes_rm_control_when_valid() {
+        let mut buffer = SECURITY_DESCRIPTOR[..20].to_vec();
+        buffer[1] = 0x2A;
+
+        let header = SecDescHeader::from_buffer(&buffer).unwrap();
+        assert_eq!(header.rm_control, 0x2A);
+        assert!(!header.is_rm_control_valid());
+        assert!(serde_json::to_value(&header)
+            .unwrap()
+            .get(key("rm_control", "rmControl"))
+            .is_none());
+
+        // SE_SELF_RELATIVE | SE_RM_CONTROL_VALID | SE_DACL_PRESENT
+        buffer[2..4].copy_from_slice(&[0x04, 0xC0]);
+
+        let header = SecDescHeader::from_buffer(&buffer).unwrap();
+        assert!(header.is_rm_control_valid());
+
+        let json = serde_json::to_value(&header).unwrap();
+        assert_eq!(json[key("rm_control", "rmControl")], 0x2A);
+
+        let deserialized: SecDescHeader = serde_json::from_value(json).unwrap();
+        assert_eq!(deserialized.rm_control, 0x2A);
+
+        // The byte is also kept when serializing a whole descriptor.
+        let mut buffer = SECURITY_DESCRIPTOR.to_vec();
+        buffer[1] = 0x2A;
+        buffer[2..4].copy_from_slice(&[0x04, 0xC0]);
+
+        let sd = SecurityDescriptor::from_buffer(&buffer).unwrap();
+        let json = serde_json::to_value(&sd).unwrap();
+        assert_eq!(json["header"][key("rm_control", "rmControl")], 0x2A);
+
+        let deserialized: SecurityDescriptor = serde_json::from_value(json).unwrap();
+        assert_eq!(deserialized.header.rm_control, 0x2A);
+        assert_eq!(deserialized, sd);
+    }
+
+    #[test]
+    fn test_retains_unknown_control_flags() {
+        let buffer: &[u8] = &[
+            0x01, 0x00, 0x44, 0x80, 0x14, 0x00, 0x00, 0x00, 0x24, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x30, 0x00, 0x00, 0x00,
+        ];
+
+        let header = SecDescHeader::from_buffer(buffer).unwrap();
+        assert_eq!(header.control_flags.bits(), 0x8044);
+        assert_eq!(header.unknown_control_flags(), 0x0040);
+
+        let json = serde_json::to_value(&header).unwrap();
+        assert_eq!(
+            json[key("control_flags", "controlFlags")],
+            "SE_DACL_PRESENT | SE_SELF_RELATIVE | 0x40"
+        );
+
+        let deserialized: SecDescHeader = serde_json::from_value(json).unwrap();
+        assert_eq!(deserialized.control_flags.bits(), 0x8044);
+    }
+
+    #[test]
+    fn test_parser_tolerates_unknown_ace_types() {
+        let mut buffer = SECURITY_DESCRIPTOR.to_vec();
+        // The type of the only ACE in the DACL.
+        buffer[0x38] = 0x30;
+
+        assert!(matches!(
+            SecurityDescriptorParser::new().parse_buffer(&buffer),
+            Err(Error::UnknownAceType { ace_type: 0x30 })
+        ));
+        assert!(SecurityDescriptor::from_buffer(&buffer).is_err());
+
+        let sd = SecurityDescriptorParser::new()
+            .tolerate_unknown_ace_types(true)
+            .parse_buffer(&buffer)
+            .unwrap();
+
+        let ace = &sd.dacl.unwrap().entries[0];
+        assert_eq!(ace.ace_type, AceType::Unknown(0x30));
+        assert_eq!(ace.ace_type.as_u8(), 0x30);
+        assert!(
+            matches!(&ace.data, AceData::Unhandled(raw) if raw.0 == SECURITY_DESCRIPTOR[0x3C..])
+        );
+    }
+
+    #[test]
+    fn test_renders_human_string() {
+        let sd = SecurityDescriptor::from_buffer(SECURITY_DESCRIPTOR).unwrap();
+        let human = sd.to_human_string();
+        let lines: Vec<&str> = human.lines().collect();
+
+        assert_eq!(lines[0], "Owner: S-1-5-32-544 (Administrators)");
+        assert_eq!(lines[1], "Group: S-1-5-18 (Local System)");
+        assert_eq!(lines[2], "Control: SE_DACL_PRESENT | SE_SELF_RELATIVE");
+        assert_eq!(lines[3], "DACL (revision 2):");
+        assert_eq!(lines[4], "  AccessAllowed");
+        assert_eq!(lines[5], "    Flags: NONE");
+        assert!(lines[6].starts_with("    Rights: SA_RIGHT_DELETE | SA_RIGHT_READCONTROL | "));
+        assert_eq!(lines[7], "    SID: S-1-1-0 (Everyone)");
+        assert_eq!(lines[8], "SACL: not present");
+        assert_eq!(lines.len(), 9);
+    }
+
+    #[test]
+    fn test_lenient_parse_collects_warnings() {
+        let (sd, warnings) =
+            SecurityDescriptor::from_buffer_lenient(REGISTRY_SECURITY_DESCRIPTOR).unwrap();
+
+        assert_eq!(
+            sd,
+            SecurityDescriptor::from_registry_hive_bytes(REGISTRY_SECURITY_DESCRIPTOR).unwrap()
+        );
+        assert_eq!(warnings.len(), 1);
+        assert!(matches!(
+            warnings[0],
+            Warning::UnknownAceType {
+                field: "SACL",
+                ace_type: 0x20
+            }
+        ));
+
+        let mut buffer = REGISTRY_SECURITY_DESCRIPTOR.to_vec();
+        // Break the header and DACL revisions, and point the group SID past the end.
+        buffer[0x00] = 0x02;
+        buffer[0x6C] = 0x07;
+        buffer[0x08] = 0xFF;
+
+        let (sd, warnings) = SecurityDescriptor::from_buffer_lenient(&buffer).unwrap();
+
+        assert_eq!(sd.owner_sid.to_string(), "S-1-5-32-544");
+        assert_eq!(sd.group_sid.to_string(), "S-1-0");
+        assert_eq!(sd.dacl.unwrap().entries.len(), 1);
+        assert_eq!(sd.sacl.unwrap().entries.len(), 4);
+        assert_eq!(
+            warnings.iter().map(|w| w.to_string()).collect::<Vec<_>>(),
+            [
+                "Unsupported security descriptor revision: 2",
+                "group SID offset 255 is outside of the 164 byte security descriptor",
+                "Unsupported ACL revision: 7",
+                "Unknown AceType in SACL: 32",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parser_validations() {
+        let mut buffer = SECURITY_DESCRIPTOR.to_vec();
+        // The revision of the DACL.
+        buffer[0x30] = 0x55;
+
+        assert!(SecurityDescriptorParser::new()
+            .parse_buffer(&buffer)
+            .is_ok());
+        assert!(matches!(
+            SecurityDescriptorParser::new()
+                .validate_revisions(true)
+                .parse_stream(&mut Cursor::new(&buffer)),
+            Err(Error::UnsupportedRevision {
+                structure: "ACL",
+                found: 0x55
+            })
+        ));
+
+        let mut buffer = SECURITY_DESCRIPTOR.to_vec();
+        // The DACL offset is within the original descriptor, but not within the truncated stream.
+        buffer[16..20].copy_from_slice(&0x4B_u32.to_le_bytes());
+        buffer.truncate(0x40);
+
+        assert!(matches!(
+            SecurityDescriptorParser::new()
+                .validate_offsets(true)
+                .parse_stream(&mut Cursor::new(&buffer)),
+            Err(Error::OffsetOutOfBounds {
+                field: "DACL",
+                offset: 0x4B,
+                len: 0x40
+            })
+        ));
+    }
+
+    proptest! {
+        #[test]
+        fn test_parsing_arbitrary_bytes_does_not_panic(buffer in proptest::collection::vec(any::<u8>(), 0..256)) {
+            let _ = SecurityDescriptor::from_buffer(&buffer);
+        }
+
+        #[test]
+        fn test_parsing_mutated_descriptor_does_not_panic(index in 0..SECURITY_DESCRIPTOR.len(), value in any::<u8>()) {
+            let mut buffer = SECURITY_DESCRIPTOR.to_vec();
+            buffer[index] = value;
+
+            let _ = SecurityDescriptor::from_buffer(&buffer);
+        }
+    }
+
+    #[cfg(feature = "schemars")]
+    #[test]
+    fn test_security_descriptor_json_schema() {
+        let schema = serde_json::to_value(schemars::schema_for!(SecurityDescriptor)).unwrap();
+
+        assert_eq!(
+            schema["properties"][key("owner_sid", "ownerSid")]["type"],
+            "string"
+        );
+        assert_eq!(
+            schema["properties"][key("group_sid", "groupSid")]["type"],
+            "string"
+        );
+
+        // The offsets are not serialized, so they are not part of the schema either.
+        let header = &schema["definitions"]["SecDescHeader"]["properties"];
+        assert!(header
+            .get(key("revision_number", "revisionNumber"))
+            .is_some());
+        assert!(header.get(key("dacl_offset", "daclOffset")).is_none());
+        assert!(header
+            .get(key("owner_sid_offset", "ownerSidOffset"))
+            .is_none());
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn test_parses_security_descriptor_async() {
+        let sd = SecurityDescriptor::from_async_stream(&mut Cursor::new(SECURITY_DESCRIPTOR))
+            .await
+            .unwrap();
+
+        assert_eq!(sd.owner_sid.to_string(), "S-1-5-32-544");
+        assert_eq!(sd.group_sid.to_string(), "S-1-5-18");
+        assert_eq!(sd.dacl.unwrap().entries.len(), 1);
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn test_async_parse_matches_sync_parse() {
+        // The descriptor is followed by unrelated data, which must be left alone.
+        let mut buffer = REGISTRY_SECURITY_DESCRIPTOR.to_vec();
+        buffer.extend_from_slice(&[0xFF; 8]);
+
+        let parser = SecurityDescriptorParser::new().tolerate_unknown_ace_types(true);
+
+        let mut stream = Cursor::new(&buffer);
+        let sd = parser.parse_async_stream(&mut stream).await.unwrap();
+
+        assert_eq!(sd, parser.parse_buffer(&buffer).unwrap());
+        assert_eq!(stream.position(), REGISTRY_SECURITY_DESCRIPTOR.len() as u64);
+
+        assert!(matches!(
+            SecurityDescriptor::from_async_stream(&mut Cursor::new(&buffer)).await,
+            Err(Error::UnknownAceType { ace_type: 0x20 })
+        ));
+
+        // The group SID points past the end of the stream.
+        buffer[0x08] = 0xFF;
+        assert!(parser
+            .parse_async_stream(&mut Cursor::new(&buffer))
+            .await
+            .is_err());
+    }
 
     #[test]
     fn test_parses_sec_desc_header() {
@@ -146,14 +1566,79 @@ mod tests {
             0x00, 0x00, 0x14, 0x00, 0x00, 0x00, 0x02, 0x00,
         ];
 
-        let header = SecDescHeader::from_buffer(&buffer).unwrap();
+        let header = SecDescHeader::from_buffer(buffer).unwrap();
 
         assert_eq!(header.revision_number, 1);
-        assert_eq!(header.padding1, 0);
+        assert_eq!(header.rm_control, 0);
         //assert_eq!(header.control_flags.bits(),38916);
         assert_eq!(header.owner_sid_offset, 152);
         assert_eq!(header.group_sid_offset, 164);
         assert_eq!(header.sacl_offset, 0);
         assert_eq!(header.dacl_offset, 20);
     }
+
+    #[test]
+    fn test_builds_sec_desc_header() {
+        let mut header =
+            SecDescHeader::new(SdControlFlags::SE_DACL_PRESENT | SdControlFlags::SE_SELF_RELATIVE);
+        header.set_owner_sid_offset(0x14);
+        header.set_group_sid_offset(0x24);
+        header.set_dacl_offset(0x30);
+
+        let mut buffer = Vec::new();
+        header.write_to(&mut buffer).unwrap();
+
+        assert_eq!(buffer.len(), SecDescHeader::SIZE);
+        assert_eq!(buffer[..], SECURITY_DESCRIPTOR[..SecDescHeader::SIZE]);
+
+        let parsed = SecDescHeader::from_buffer(&buffer).unwrap();
+        assert_eq!(parsed.revision_number, 1);
+        assert_eq!(parsed.control_flags, header.control_flags);
+        assert_eq!(parsed.owner_sid_offset, 0x14);
+        assert_eq!(parsed.group_sid_offset, 0x24);
+        assert_eq!(parsed.sacl_offset, 0);
+        assert_eq!(parsed.dacl_offset, 0x30);
+    }
+
+    #[test]
+    fn test_writes_security_descriptor() {
+        let sd = SecurityDescriptor::from_buffer(SECURITY_DESCRIPTOR).unwrap();
+
+        let mut buffer = Vec::new();
+        sd.write_to(&mut buffer).unwrap();
+        assert_eq!(buffer, SECURITY_DESCRIPTOR);
+
+        let sd =
+            SecurityDescriptor::from_registry_hive_bytes(REGISTRY_SECURITY_DESCRIPTOR).unwrap();
+
+        let mut buffer = Vec::new();
+        sd.write_to(&mut buffer).unwrap();
+        assert_eq!(buffer.len(), sd.byte_len());
+        assert_eq!(
+            SecurityDescriptor::from_registry_hive_bytes(&buffer).unwrap(),
+            sd
+        );
+    }
+
+    #[test]
+    fn test_write_sets_layout_flags() {
+        let parsed = SecurityDescriptor::from_buffer(SECURITY_DESCRIPTOR).unwrap();
+        let sd = SecurityDescriptor {
+            header: SecDescHeader::new(SdControlFlags::SE_SACL_PRESENT),
+            sacl: None,
+            ..parsed.clone()
+        };
+
+        let mut buffer = Vec::new();
+        sd.write_to(&mut buffer).unwrap();
+
+        let written = SecurityDescriptor::from_buffer(&buffer).unwrap();
+        assert_eq!(
+            written.header.control_flags,
+            SdControlFlags::SE_SELF_RELATIVE | SdControlFlags::SE_DACL_PRESENT
+        );
+        assert_eq!(written.owner_sid, parsed.owner_sid);
+        assert_eq!(written.dacl, parsed.dacl);
+        assert_eq!(written.sacl, None);
+    }
 }
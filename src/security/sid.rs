@@ -1,14 +1,40 @@
 //! SID
 //! https://github.com/libyal/libfwnt/wiki/Security-Descriptor#security-identifier
-use crate::err::Result;
-use crate::security::authority::{Authority, SubAuthorityList};
-use byteorder::ReadBytesExt;
-use serde::ser;
+use crate::err::{Error, Result};
+use crate::security::authority::{Authority, SubAuthority, SubAuthorityList};
+use crate::security::well_known;
+use byteorder::{BigEndian, ByteOrder, LittleEndian, ReadBytesExt, WriteBytesExt};
+use serde::{de, ser};
+#[cfg(feature = "tokio")]
+use tokio::io::{AsyncRead, AsyncReadExt};
 
 use std::fmt;
-use std::io::{Cursor, Read};
+use std::io::{Cursor, Read, Write};
+use std::str::FromStr;
 
-#[derive(Debug, Clone, PartialOrd, PartialEq)]
+/// The broad category of a SID, based on its authority and leading sub authorities.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SidKind {
+    /// One of the fixed SIDs in `well_known`, such as `S-1-1-0` or `S-1-5-18`.
+    WellKnown,
+    /// A local built-in group (`S-1-5-32-...`).
+    BuiltIn,
+    /// A domain or local machine account (`S-1-5-21-...`).
+    Domain,
+    /// A logon session (`S-1-5-5-X-Y`).
+    LogonSession,
+    /// A service SID (`S-1-5-80-...`).
+    Service,
+    /// An app container package SID (`S-1-15-2-...`).
+    Package,
+    /// An app container capability SID (`S-1-15-3-...`).
+    Capability,
+    /// A mandatory integrity level (`S-1-16-...`).
+    IntegrityLevel,
+    Other,
+}
+
+#[derive(Debug, Clone, PartialOrd, PartialEq, Eq, Hash)]
 pub struct Sid {
     revision_number: u8,
     sub_authority_count: u8,
@@ -17,15 +43,100 @@ pub struct Sid {
 }
 
 impl Sid {
+    pub const SID_REVISION: u8 = 1;
+    pub const MAX_SUB_AUTHORITIES: usize = 15;
+    /// The size of the largest possible SID (`SECURITY_MAX_SID_SIZE`).
+    pub const MAX_SIZE: usize = 8 + 4 * Sid::MAX_SUB_AUTHORITIES;
+
+    /// Builds a SID from its parts, e.g. `Sid::new(1, 5, &[18])` for `S-1-5-18`.
+    pub fn new(revision: u8, authority: u64, sub_authorities: &[u32]) -> Result<Sid> {
+        if sub_authorities.len() > Sid::MAX_SUB_AUTHORITIES {
+            return Err(Error::TooManySubAuthorities {
+                count: sub_authorities.len(),
+            });
+        }
+
+        if authority >= 1 << 48 {
+            return Err(Error::AuthorityOutOfRange { authority });
+        }
+
+        Ok(Sid {
+            revision_number: revision,
+            sub_authority_count: sub_authorities.len() as u8,
+            authority: Authority(authority),
+            sub_authorities: SubAuthorityList(
+                sub_authorities.iter().copied().map(SubAuthority).collect(),
+            ),
+        })
+    }
+
     pub fn from_buffer(buffer: &[u8]) -> Result<Self> {
         Self::from_reader(&mut Cursor::new(buffer))
     }
 
+    /// Parses a SID from the start of `buffer`, also returning the number of bytes it occupied,
+    /// so parsing of an enclosing structure can continue right after it.
+    pub fn from_buffer_with_len(buffer: &[u8]) -> Result<(Self, usize)> {
+        let sid = Self::from_buffer(buffer)?;
+        let len = sid.byte_len();
+
+        Ok((sid, len))
+    }
+
+    /// Parses a SID stored in a fixed buffer of `MAX_SIZE` bytes, as found in tokens, where the SID
+    /// only occupies a prefix and the rest is slack. The SID may not extend past the buffer,
+    /// or past the first `MAX_SIZE` bytes of it.
+    pub fn from_fixed_buffer(buffer: &[u8]) -> Result<Sid> {
+        let buffer = &buffer[..buffer.len().min(Sid::MAX_SIZE)];
+
+        Self::from_buffer(buffer)
+    }
+
     pub fn from_reader<R: Read>(reader: &mut R) -> Result<Sid> {
+        Self::read::<R, BigEndian>(reader, false)
+    }
+
+    /// Like `from_reader`, but errors if the SID revision is not a known one.
+    pub fn from_reader_validated<R: Read>(reader: &mut R) -> Result<Sid> {
+        Self::read::<R, BigEndian>(reader, true)
+    }
+
+    /// Like `from_reader`, but reads the authority with the byte order `B` instead of big endian.
+    /// Useful when recovering SIDs written by tools that stored the authority little endian.
+    /// The sub authorities are always little endian.
+    pub fn from_reader_with_authority_endian<R: Read, B: ByteOrder>(reader: &mut R) -> Result<Sid> {
+        Self::read::<R, B>(reader, false)
+    }
+
+    /// Reads a SID preceded by its length as a little endian `u32`, as found in token and claim structures.
+    /// Errors if the length doesn't match the size of the SID.
+    pub fn from_reader_length_prefixed<R: Read>(reader: &mut R) -> Result<Sid> {
+        let declared = reader.read_u32::<LittleEndian>()?;
+        let sid = Self::from_reader(reader)?;
+
+        if u64::from(declared) != sid.byte_len() as u64 {
+            return Err(Error::SidLengthMismatch {
+                declared,
+                actual: sid.byte_len(),
+            });
+        }
+
+        Ok(sid)
+    }
+
+    fn read<R: Read, B: ByteOrder>(reader: &mut R, validate: bool) -> Result<Sid> {
         let revision_number = reader.read_u8()?;
+
+        if validate && revision_number != Sid::SID_REVISION {
+            return Err(Error::UnsupportedRevision {
+                structure: "SID",
+                found: revision_number,
+            });
+        }
+
         let sub_authority_count = reader.read_u8()?;
 
-        let authority = Authority::from_reader(reader)?;
+        let authority = Authority::from_reader_endian::<R, B>(reader)?;
         let sub_authorities = SubAuthorityList::from_reader(reader, sub_authority_count)?;
 
         Ok(Sid {
@@ -35,6 +146,174 @@ impl Sid {
             sub_authorities,
         })
     }
+
+    /// Writes the SID in its binary form, the inverse of `from_reader`.
+    ///
+    /// Errors with `AuthorityOutOfRange` if the authority does not fit in 48 bits.
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> Result<()> {
+        if self.authority.0 >= 1 << 48 {
+            return Err(Error::AuthorityOutOfRange {
+                authority: self.authority.0,
+            });
+        }
+
+        writer.write_u8(self.revision_number)?;
+        writer.write_u8(self.sub_authority_count)?;
+        writer.write_u48::<BigEndian>(self.authority.0)?;
+
+        for sub_authority in self.sub_authorities.0.iter() {
+            writer.write_u32::<LittleEndian>(sub_authority.0)?;
+        }
+
+        Ok(())
+    }
+
+    /// The SID in the binary form Windows uses in memory, as produced by `ConvertStringSidToSid`.
+    /// A pointer to this buffer can be cast to a `PSID` for Win32 APIs (such as through the `windows` crate),
+    /// as long as the buffer outlives the call.
+    pub fn to_windows_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.byte_len());
+        self.write_to(&mut bytes).expect(
+            "Writing to a Vec cannot fail, and the constructors keep the authority within 48 bits",
+        );
+
+        bytes
+    }
+
+    pub fn authority(&self) -> &Authority {
+        &self.authority
+    }
+
+    pub fn sub_authorities(&self) -> &SubAuthorityList {
+        &self.sub_authorities
+    }
+
+    /// The name of the SID, if it is one of the SIDs in `well_known`.
+    pub fn well_known_name(&self) -> Option<&'static str> {
+        well_known::lookup(self).map(|well_known| well_known.name)
+    }
+
+    /// Like `to_string`, but followed by the well known name of the SID in parentheses when it has one,
+    /// such as `S-1-5-18 (Local System)`.
+    pub fn to_string_named(&self) -> String {
+        match self.well_known_name() {
+            Some(name) => format!("{} ({})", self, name),
+            None => self.to_string(),
+        }
+    }
+
+    pub fn classify(&self) -> SidKind {
+        let first_sub_authority = self
+            .sub_authorities
+            .0
+            .first()
+            .map(|sub_authority| sub_authority.0);
+
+        match (self.authority.0, first_sub_authority) {
+            (5, Some(5)) => SidKind::LogonSession,
+            (5, Some(21)) => SidKind::Domain,
+            (5, Some(32)) => SidKind::BuiltIn,
+            (5, Some(80)) => SidKind::Service,
+            (15, Some(2)) => SidKind::Package,
+            (15, Some(3)) => SidKind::Capability,
+            (16, Some(_)) => SidKind::IntegrityLevel,
+            _ if well_known::lookup(self).is_some() => SidKind::WellKnown,
+            _ => SidKind::Other,
+        }
+    }
+
+    /// For a domain account SID (`S-1-5-21-X-Y-Z-RID`), the SID of its domain (`S-1-5-21-X-Y-Z`).
+    pub fn domain_sid(&self) -> Option<Sid> {
+        let (domain, _) = self.split_domain_account()?;
+
+        Some(Sid {
+            revision_number: self.revision_number,
+            sub_authority_count: domain.len() as u8,
+            authority: self.authority.clone(),
+            sub_authorities: SubAuthorityList(domain.into()),
+        })
+    }
+
+    /// For a domain account SID (`S-1-5-21-X-Y-Z-RID`), its relative identifier.
+    pub fn rid(&self) -> Option<u32> {
+        self.split_domain_account().map(|(_, rid)| rid.0)
+    }
+
+    /// The SID of the account `rid` relative to this SID, the inverse of `domain_sid` and `rid`.
+    /// Errors if this SID already has the maximum number of sub authorities.
+    pub fn with_rid(&self, rid: u32) -> Result<Sid> {
+        let count = self.sub_authorities.len() + 1;
+
+        if count > Sid::MAX_SUB_AUTHORITIES {
+            return Err(Error::TooManySubAuthorities { count });
+        }
+
+        let mut sid = self.clone();
+        sid.sub_authorities.0.push(SubAuthority(rid));
+        sid.sub_authority_count = count as u8;
+
+        Ok(sid)
+    }
+
+    fn split_domain_account(&self) -> Option<(&[SubAuthority], &SubAuthority)> {
+        if self.classify() != SidKind::Domain || self.sub_authorities.len() != 5 {
+            return None;
+        }
+
+        let (rid, domain) = self.sub_authorities.0.split_last()?;
+
+        Some((domain, rid))
+    }
+
+    /// The size of the SID in bytes: the 8 byte header followed by the sub authorities.
+    pub fn byte_len(&self) -> usize {
+        8 + 4 * self.sub_authorities.0.len()
+    }
+
+    pub(crate) fn matches(&self, revision: u8, authority: u64, sub_authorities: &[u32]) -> bool {
+        self.revision_number == revision
+            && self.authority.0 == authority
+            && self
+                .sub_authorities
+                .0
+                .iter()
+                .map(|sub_authority| sub_authority.0)
+                .eq(sub_authorities.iter().copied())
+    }
+
+    #[cfg(feature = "tokio")]
+    pub async fn from_async_reader<R: AsyncRead + Unpin>(reader: &mut R) -> Result<Sid> {
+        // Revision, sub authority count and the 6 byte authority.
+        let mut buffer = vec![0; 8];
+        reader.read_exact(&mut buffer).await?;
+
+        let sub_authority_count = buffer[1];
+        buffer.resize(8 + 4 * usize::from(sub_authority_count), 0);
+        reader.read_exact(&mut buffer[8..]).await?;
+
+        Self::from_buffer(&buffer)
+    }
+}
+
+/// A SID has at most 15 sub authorities, the count must agree with the list,
+/// and the authority must fit in 48 bits.
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for Sid {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        let sub_authority_count = u.int_in_range(0..=15)?;
+        let mut sub_authorities = Vec::with_capacity(usize::from(sub_authority_count));
+
+        for _ in 0..sub_authority_count {
+            sub_authorities.push(u.arbitrary()?);
+        }
+
+        Ok(Sid {
+            revision_number: u.arbitrary()?,
+            sub_authority_count,
+            authority: Authority(u.int_in_range(0..=(1 << 48) - 1)?),
+            sub_authorities: SubAuthorityList(sub_authorities.into_iter().collect()),
+        })
+    }
 }
 
 impl fmt::Display for Sid {
@@ -42,13 +321,77 @@ impl fmt::Display for Sid {
         write!(
             f,
             "S-{}-{}{}",
-            self.revision_number,
-            self.authority,
-            self.sub_authorities.to_string()
+            self.revision_number, self.authority, self.sub_authorities
         )
     }
 }
 
+/// Parses the `S-R-I-S-S...` string form of a SID.
+/// The authority may also be given in hex (`0x...`), as Windows does for large authorities.
+impl FromStr for Sid {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Sid> {
+        let invalid = || Error::InvalidSidString {
+            value: s.to_string(),
+        };
+
+        let mut parts = s.split('-');
+
+        if !parts
+            .next()
+            .is_some_and(|prefix| prefix.eq_ignore_ascii_case("S"))
+        {
+            return Err(invalid());
+        }
+
+        let revision_number = parts
+            .next()
+            .and_then(|revision| revision.parse::<u8>().ok())
+            .ok_or_else(invalid)?;
+
+        let authority = parts
+            .next()
+            .and_then(|authority| match authority.strip_prefix("0x") {
+                Some(hex) => u64::from_str_radix(hex, 16).ok(),
+                None => authority.parse::<u64>().ok(),
+            })
+            .filter(|authority| *authority < 1 << 48)
+            .ok_or_else(invalid)?;
+
+        let sub_authorities = parts
+            .map(|sub_authority| sub_authority.parse::<u32>().map(SubAuthority))
+            .collect::<std::result::Result<Vec<SubAuthority>, _>>()
+            .map_err(|_| invalid())?;
+
+        if sub_authorities.len() > Sid::MAX_SUB_AUTHORITIES {
+            return Err(invalid());
+        }
+
+        Ok(Sid {
+            revision_number,
+            sub_authority_count: sub_authorities.len() as u8,
+            authority: Authority(authority),
+            sub_authorities: SubAuthorityList(sub_authorities.into_iter().collect()),
+        })
+    }
+}
+
+/// Compares against the string form of a SID, which is parsed (so `s-1-5-18` also matches).
+impl PartialEq<str> for Sid {
+    fn eq(&self, other: &str) -> bool {
+        other.parse::<Sid>().is_ok_and(|other| *self == other)
+    }
+}
+
+impl PartialEq<&str> for Sid {
+    fn eq(&self, other: &&str) -> bool {
+        *self == **other
+    }
+}
+
+impl_json_schema_as_string!(Sid);
+
 impl ser::Serialize for Sid {
     fn serialize<S>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error>
     where
@@ -58,9 +401,152 @@ impl ser::Serialize for Sid {
     }
 }
 
+impl<'de> de::Deserialize<'de> for Sid {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(de::Error::custom)
+    }
+}
+
+/// A pattern over SIDs, such as `S-1-5-21-**-500` for the built-in administrator of any domain.
+///
+/// Any component can be `*`, which matches any single value. In the sub authorities,
+/// `**` matches any run of them, including an empty one.
+///
+/// # Example
+///
+/// ```
+/// # use winstructs::security::{Sid, SidPattern};
+/// let pattern: SidPattern = "S-1-5-21-*-*-*-1001".parse().unwrap();
+///
+/// assert!(pattern.matches(&"S-1-5-21-1004336348-1177238915-682003330-1001".parse().unwrap()));
+/// assert!(!pattern.matches(&"S-1-5-21-1004336348-1177238915-682003330-500".parse().unwrap()));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SidPattern {
+    revision: Option<u8>,
+    authority: Option<u64>,
+    sub_authorities: Vec<SubAuthorityPattern>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum SubAuthorityPattern {
+    Value(u32),
+    Any,
+    AnyRun,
+}
+
+impl SidPattern {
+    pub fn matches(&self, sid: &Sid) -> bool {
+        self.revision
+            .is_none_or(|revision| revision == sid.revision_number)
+            && self
+                .authority
+                .is_none_or(|authority| authority == sid.authority.0)
+            && matches_sub_authorities(&self.sub_authorities, &sid.sub_authorities.0)
+    }
+}
+
+/// Matches like a glob, with `**` as the star: on a mismatch, only the most recent `**` has to
+/// absorb one more value, since any earlier one could not lead to a match that the later one misses.
+/// This bounds the work by the product of the lengths, however many `**` the pattern has.
+fn matches_sub_authorities(pattern: &[SubAuthorityPattern], values: &[SubAuthority]) -> bool {
+    let (mut p, mut v) = (0, 0);
+    // The position after the last `**`, and the first value it has not absorbed yet.
+    let mut backtrack = None;
+
+    while v < values.len() {
+        match pattern.get(p) {
+            Some(SubAuthorityPattern::AnyRun) => {
+                p += 1;
+                backtrack = Some((p, v));
+            }
+            Some(SubAuthorityPattern::Any) => {
+                p += 1;
+                v += 1;
+            }
+            Some(SubAuthorityPattern::Value(value)) if *value == values[v].0 => {
+                p += 1;
+                v += 1;
+            }
+            _ => match backtrack {
+                Some((after_run, absorbed)) => {
+                    p = after_run;
+                    v = absorbed + 1;
+                    backtrack = Some((after_run, v));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..]
+        .iter()
+        .all(|part| *part == SubAuthorityPattern::AnyRun)
+}
+
+impl FromStr for SidPattern {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<SidPattern> {
+        let invalid = || Error::InvalidSidPattern {
+            value: s.to_string(),
+        };
+
+        let mut parts = s.split('-');
+
+        if !parts
+            .next()
+            .is_some_and(|prefix| prefix.eq_ignore_ascii_case("S"))
+        {
+            return Err(invalid());
+        }
+
+        let revision = match parts.next().ok_or_else(invalid)? {
+            "*" => None,
+            revision => Some(revision.parse::<u8>().map_err(|_| invalid())?),
+        };
+
+        let authority = match parts.next().ok_or_else(invalid)? {
+            "*" => None,
+            authority => Some(
+                match authority.strip_prefix("0x") {
+                    Some(hex) => u64::from_str_radix(hex, 16).ok(),
+                    None => authority.parse::<u64>().ok(),
+                }
+                .filter(|authority| *authority < 1 << 48)
+                .ok_or_else(invalid)?,
+            ),
+        };
+
+        let sub_authorities = parts
+            .map(|part| match part {
+                "*" => Ok(SubAuthorityPattern::Any),
+                "**" => Ok(SubAuthorityPattern::AnyRun),
+                value => value.parse::<u32>().map(SubAuthorityPattern::Value),
+            })
+            .collect::<std::result::Result<Vec<SubAuthorityPattern>, _>>()
+            .map_err(|_| invalid())?;
+
+        Ok(SidPattern {
+            revision,
+            authority,
+            sub_authorities,
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::security::sid::Sid;
+    use crate::err::Error;
+    use crate::security::authority::Authority;
+    use crate::security::sid::{Sid, SidKind, SidPattern};
+    use byteorder::{BigEndian, LittleEndian};
+    use std::io::Cursor;
 
     #[test]
     fn test_parses_sid() {
@@ -71,5 +557,285 @@ mod tests {
         let sid = Sid::from_buffer(buffer).unwrap();
 
         assert_eq!(format!("{}", sid), "S-1-5-18");
+        assert_eq!(sid.byte_len(), buffer.len());
+    }
+
+    #[test]
+    fn test_parses_sid_from_fixed_buffer() {
+        let mut buffer = [0; Sid::MAX_SIZE];
+        buffer[..12].copy_from_slice(&[
+            0x01, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x05, 0x12, 0x00, 0x00, 0x00,
+        ]);
+
+        let sid = Sid::from_fixed_buffer(&buffer).unwrap();
+        assert_eq!(sid.to_string(), "S-1-5-18");
+
+        // A count of 16 sub authorities would extend past the 68 byte buffer.
+        let mut buffer = vec![0xFF; 80];
+        buffer[..2].copy_from_slice(&[0x01, 0x10]);
+        assert!(matches!(
+            Sid::from_fixed_buffer(&buffer),
+            Err(Error::TruncatedSid {
+                read: 15,
+                expected: 16
+            })
+        ));
+    }
+
+    #[test]
+    fn test_truncated_sub_authorities() {
+        // Declares 5 sub authorities, but only holds 3.
+        let buffer: &[u8] = &[
+            0x01, 0x05, 0x00, 0x00, 0x00, 0x00, 0x00, 0x05, 0x15, 0x00, 0x00, 0x00, 0x01, 0x00,
+            0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x03, 0x00,
+        ];
+
+        assert!(matches!(
+            Sid::from_buffer(buffer),
+            Err(Error::TruncatedSid {
+                read: 3,
+                expected: 5
+            })
+        ));
+    }
+
+    #[test]
+    fn test_parses_length_prefixed_sid() {
+        let mut buffer: Vec<u8> = vec![
+            0x0C, 0x00, 0x00, 0x00, // length
+            0x01, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x05, 0x12, 0x00, 0x00, 0x00,
+        ];
+
+        let sid = Sid::from_reader_length_prefixed(&mut Cursor::new(&buffer)).unwrap();
+        assert_eq!(sid.to_string(), "S-1-5-18");
+
+        buffer[0] = 0x10;
+        assert!(matches!(
+            Sid::from_reader_length_prefixed(&mut Cursor::new(&buffer)),
+            Err(Error::SidLengthMismatch {
+                declared: 0x10,
+                actual: 12
+            })
+        ));
+    }
+
+    #[test]
+    fn test_parses_sid_with_authority_endian() {
+        // S-1-5-18, with the authority stored little endian.
+        let buffer: &[u8] = &[
+            0x01, 0x01, 0x05, 0x00, 0x00, 0x00, 0x00, 0x00, 0x12, 0x00, 0x00, 0x00,
+        ];
+
+        let big = Sid::from_reader_with_authority_endian::<_, BigEndian>(&mut Cursor::new(buffer))
+            .unwrap();
+        assert_eq!(big.authority().value(), 0x0500_0000_0000);
+        assert_eq!(big, Sid::from_buffer(buffer).unwrap());
+
+        let little =
+            Sid::from_reader_with_authority_endian::<_, LittleEndian>(&mut Cursor::new(buffer))
+                .unwrap();
+        assert_eq!(little.authority().value(), 5);
+        assert_eq!(little.to_string(), "S-1-5-18");
+    }
+
+    #[test]
+    fn test_sid_to_windows_bytes() {
+        let sid: Sid = "S-1-5-18".parse().unwrap();
+
+        assert_eq!(
+            sid.to_windows_bytes(),
+            [0x01, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x05, 0x12, 0x00, 0x00, 0x00]
+        );
+
+        let sid: Sid = "S-1-5-21-1004336348-1177238915-682003330-512"
+            .parse()
+            .unwrap();
+        let bytes = sid.to_windows_bytes();
+
+        assert_eq!(bytes.len(), sid.byte_len());
+        assert_eq!(Sid::from_buffer(&bytes).unwrap(), sid);
+    }
+
+    #[test]
+    fn test_write_rejects_authority_out_of_range() {
+        let sid = Sid {
+            authority: Authority(1 << 48),
+            ..Sid::new(1, 5, &[18]).unwrap()
+        };
+        let mut bytes = Vec::new();
+
+        assert!(matches!(
+            sid.write_to(&mut bytes),
+            Err(Error::AuthorityOutOfRange { authority }) if authority == 1 << 48
+        ));
+        assert!(bytes.is_empty());
+    }
+
+    #[test]
+    fn test_sid_to_string_named() {
+        let sid: Sid = "S-1-5-18".parse().unwrap();
+        assert_eq!(sid.well_known_name(), Some("Local System"));
+        assert_eq!(sid.to_string_named(), "S-1-5-18 (Local System)");
+        assert_eq!(sid.to_string(), "S-1-5-18");
+
+        let sid: Sid = "S-1-5-21-1-2-3-500".parse().unwrap();
+        assert_eq!(sid.well_known_name(), None);
+        assert_eq!(sid.to_string_named(), "S-1-5-21-1-2-3-500");
+    }
+
+    #[test]
+    fn test_parses_sid_with_len() {
+        let buffer: &[u8] = &[
+            0x01, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x05, 0x12, 0x00, 0x00, 0x00, 0xFF, 0xFF,
+        ];
+
+        let (sid, len) = Sid::from_buffer_with_len(buffer).unwrap();
+
+        assert_eq!(sid.to_string(), "S-1-5-18");
+        assert_eq!(len, 12);
+        assert_eq!(&buffer[len..], &[0xFF, 0xFF]);
+    }
+
+    #[test]
+    fn test_sid_eq_str() {
+        let sid: Sid = "S-1-5-18".parse().unwrap();
+
+        assert!(sid == "S-1-5-18");
+        assert!(sid == *"s-1-5-18");
+        assert!(sid != "S-1-5-19");
+        assert!(sid != "S-1-5-18-1");
+        assert!(sid != "not a sid");
+    }
+
+    #[test]
+    fn test_validates_sid_revision() {
+        let buffer: &[u8] = &[
+            0x02, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x05, 0x12, 0x00, 0x00, 0x00,
+        ];
+
+        assert!(Sid::from_buffer(buffer).is_ok());
+        assert!(matches!(
+            Sid::from_reader_validated(&mut Cursor::new(buffer)),
+            Err(Error::UnsupportedRevision {
+                structure: "SID",
+                found: 2
+            })
+        ));
+    }
+
+    #[test]
+    fn test_builds_sid() {
+        let sid = Sid::new(1, 5, &[21, 1, 2, 3, 1001]).unwrap();
+
+        assert_eq!(sid.to_string(), "S-1-5-21-1-2-3-1001");
+        assert_eq!(sid, "S-1-5-21-1-2-3-1001".parse::<Sid>().unwrap());
+
+        assert!(matches!(
+            Sid::new(1, 5, &[0; 16]),
+            Err(Error::TooManySubAuthorities { count: 16 })
+        ));
+        assert!(matches!(
+            Sid::new(1, 1 << 48, &[18]),
+            Err(Error::AuthorityOutOfRange { authority }) if authority == 1 << 48
+        ));
+        assert!(Sid::new(1, (1 << 48) - 1, &[18]).is_ok());
+    }
+
+    #[test]
+    fn test_sid_with_rid() {
+        let domain: Sid = "S-1-5-21-1004336348-1177238915-682003330".parse().unwrap();
+        let administrator = domain.with_rid(500).unwrap();
+
+        assert_eq!(
+            administrator.to_string(),
+            "S-1-5-21-1004336348-1177238915-682003330-500"
+        );
+        assert_eq!(administrator.byte_len(), domain.byte_len() + 4);
+        assert_eq!(administrator.domain_sid(), Some(domain.clone()));
+        assert_eq!(administrator.rid(), Some(500));
+
+        assert_eq!(domain.domain_sid(), None);
+        assert_eq!("S-1-5-32-544".parse::<Sid>().unwrap().rid(), None);
+
+        let full = Sid::new(1, 5, &[0; 15]).unwrap();
+        assert!(matches!(
+            full.with_rid(500),
+            Err(Error::TooManySubAuthorities { count: 16 })
+        ));
+    }
+
+    #[test]
+    fn test_sid_pattern() {
+        let matches = |pattern: &str, sid: &str| {
+            pattern
+                .parse::<SidPattern>()
+                .unwrap()
+                .matches(&sid.parse().unwrap())
+        };
+
+        let admin = "S-1-5-21-1004336348-1177238915-682003330-500";
+
+        assert!(matches("S-1-5-21-*-*-*-500", admin));
+        assert!(matches("S-1-5-21-**-500", admin));
+        assert!(matches("S-1-*-**", admin));
+        assert!(matches("S-1-5-21-1004336348-**", admin));
+        assert!(!matches("S-1-5-21-*-500", admin));
+        assert!(!matches("S-1-5-21-**-1001", admin));
+        assert!(!matches("S-1-5-32-**", admin));
+
+        // `**` may match no sub authorities at all.
+        assert!(matches("S-1-5-**-18", "S-1-5-18"));
+        assert!(!matches("S-1-5-*-18", "S-1-5-18"));
+        assert!(matches("S-1-5-**-**-18-**", "S-1-5-18"));
+        assert!(matches("S-1-5-**-*-18", "S-1-5-21-18-18"));
+        assert!(!matches("S-1-5-**-21-*-**-18", "S-1-5-21-18"));
+
+        // Many `**` which cannot match must not take exponential time to reject.
+        let pattern = format!("S-1-5-{}2", "**-1-".repeat(64));
+        let sid = format!("S-1-5-{}", ["1"; 15].join("-"));
+        assert!(!matches(&pattern, &sid));
+
+        assert!(matches!(
+            "S-1-5-***".parse::<SidPattern>(),
+            Err(Error::InvalidSidPattern { .. })
+        ));
+        assert!("S-1-**-18".parse::<SidPattern>().is_err());
+        assert!("X-1-5".parse::<SidPattern>().is_err());
+    }
+
+    #[test]
+    fn test_classifies_sids() {
+        let classify = |s: &str| s.parse::<Sid>().unwrap().classify();
+
+        assert_eq!(
+            classify("S-1-5-21-1004336348-1177238915-682003330-1001"),
+            SidKind::Domain
+        );
+        assert_eq!(
+            classify("S-1-15-3-1024-1065365936-1281604716-3511738428-1654721687"),
+            SidKind::Capability
+        );
+        assert_eq!(classify("S-1-15-2-1"), SidKind::Package);
+        assert_eq!(classify("S-1-5-5-0-123456"), SidKind::LogonSession);
+        assert_eq!(classify("S-1-5-32-544"), SidKind::BuiltIn);
+        assert_eq!(classify("S-1-5-18"), SidKind::WellKnown);
+        assert_eq!(classify("S-1-16-12288"), SidKind::IntegrityLevel);
+        assert_eq!(classify("S-1-9-1"), SidKind::Other);
+    }
+
+    #[test]
+    fn test_sid_from_str() {
+        let buffer: &[u8] = &[
+            0x01, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x05, 0x20, 0x00, 0x00, 0x00, 0x20, 0x02,
+            0x00, 0x00,
+        ];
+
+        let sid: Sid = "S-1-5-32-544".parse().unwrap();
+
+        assert_eq!(sid, Sid::from_buffer(buffer).unwrap());
+        assert_eq!(sid.to_string(), "S-1-5-32-544");
+        assert!("S-1-5-32-".parse::<Sid>().is_err());
+        assert!("X-1-5-32".parse::<Sid>().is_err());
+        assert!("S-1".parse::<Sid>().is_err());
     }
 }
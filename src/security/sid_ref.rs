@@ -0,0 +1,115 @@
+//! A borrowed, non-allocating view of a SID.
+use crate::err::Result;
+use crate::security::sid::Sid;
+use byteorder::{BigEndian, ByteOrder, LittleEndian};
+
+use std::fmt;
+use std::io;
+
+/// A SID borrowed from its binary representation.
+///
+/// Useful when scanning many SIDs only to compare or format them,
+/// since it doesn't allocate. Use `into_owned` to get a `Sid`.
+/// # Example
+///
+/// ```
+/// # use winstructs::security::SidRef;
+/// let raw_sid: &[u8] = &[0x01, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x05, 0x12, 0x00, 0x00, 0x00];
+///
+/// let sid = SidRef::from_buffer(raw_sid).unwrap();
+///
+/// assert_eq!(sid.authority(), 5);
+/// assert_eq!(sid.sub_authorities().collect::<Vec<u32>>(), vec![18]);
+/// assert_eq!(format!("{}", sid), "S-1-5-18");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SidRef<'a> {
+    buffer: &'a [u8],
+}
+
+impl<'a> SidRef<'a> {
+    /// Borrows the SID at the start of the buffer, any trailing data is ignored.
+    pub fn from_buffer(buffer: &'a [u8]) -> Result<SidRef<'a>> {
+        if buffer.len() < 8 {
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+        }
+
+        let len = 8 + 4 * usize::from(buffer[1]);
+
+        if buffer.len() < len {
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+        }
+
+        Ok(SidRef {
+            buffer: &buffer[..len],
+        })
+    }
+
+    pub fn revision_number(&self) -> u8 {
+        self.buffer[0]
+    }
+
+    pub fn sub_authority_count(&self) -> u8 {
+        self.buffer[1]
+    }
+
+    /// The 48-bit identifier authority, which is stored big-endian.
+    pub fn authority(&self) -> u64 {
+        BigEndian::read_u48(&self.buffer[2..8])
+    }
+
+    pub fn sub_authorities(&self) -> impl Iterator<Item = u32> + 'a {
+        self.buffer[8..].chunks_exact(4).map(LittleEndian::read_u32)
+    }
+
+    /// The bytes making up the SID.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.buffer
+    }
+
+    pub fn into_owned(self) -> Sid {
+        Sid::from_buffer(self.buffer).expect("length was validated when borrowing the SID")
+    }
+}
+
+impl<'a> fmt::Display for SidRef<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "S-{}-{}", self.revision_number(), self.authority())?;
+
+        for sub_authority in self.sub_authorities() {
+            write!(f, "-{}", sub_authority)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::security::sid::Sid;
+    use crate::security::sid_ref::SidRef;
+
+    #[test]
+    fn test_sid_ref_matches_owned_sid() {
+        let buffer: &[u8] = &[
+            0x01, 0x05, 0x00, 0x00, 0x00, 0x00, 0x00, 0x05, 0x15, 0x00, 0x00, 0x00, 0x01, 0x00,
+            0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00, 0xE9, 0x03, 0x00, 0x00,
+            0xFF, 0xFF,
+        ];
+
+        let sid_ref = SidRef::from_buffer(buffer).unwrap();
+        let sid = Sid::from_buffer(buffer).unwrap();
+
+        assert_eq!(sid_ref.to_string(), "S-1-5-21-1-2-3-1001");
+        assert_eq!(sid_ref.to_string(), sid.to_string());
+        assert_eq!(sid_ref.as_bytes().len(), 28);
+        assert_eq!(sid_ref.into_owned(), sid);
+    }
+
+    #[test]
+    fn test_sid_ref_truncated() {
+        let buffer: &[u8] = &[0x01, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x05, 0x12, 0x00];
+
+        assert!(SidRef::from_buffer(buffer).is_err());
+    }
+}
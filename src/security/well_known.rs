@@ -0,0 +1,172 @@
+//! Well known SIDs.
+//! https://docs.microsoft.com/en-us/windows/win32/secauthz/well-known-sids
+//!
+//! Since a `Sid` owns its sub authorities, these are described by `WellKnownSid`,
+//! which compares directly against a parsed `Sid`.
+//!
+//! ```
+//! # use winstructs::security::{well_known, Sid};
+//! let raw_sid: &[u8] = &[0x01, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x05, 0x12, 0x00, 0x00, 0x00];
+//!
+//! let sid = Sid::from_buffer(raw_sid).unwrap();
+//!
+//! assert!(sid == well_known::LOCAL_SYSTEM);
+//! assert_eq!(well_known::lookup(&sid).unwrap().name, "Local System");
+//! ```
+use crate::security::sid::Sid;
+
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct WellKnownSid {
+    pub name: &'static str,
+    pub authority: u64,
+    pub sub_authorities: &'static [u32],
+}
+
+impl WellKnownSid {
+    const fn new(name: &'static str, authority: u64, sub_authorities: &'static [u32]) -> Self {
+        WellKnownSid {
+            name,
+            authority,
+            sub_authorities,
+        }
+    }
+
+    pub fn to_sid(&self) -> Sid {
+        self.to_string()
+            .parse()
+            .expect("well known SIDs are always valid")
+    }
+}
+
+impl fmt::Display for WellKnownSid {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "S-{}-{}", Sid::SID_REVISION, self.authority)?;
+
+        for sub_authority in self.sub_authorities {
+            write!(f, "-{}", sub_authority)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl PartialEq<WellKnownSid> for Sid {
+    fn eq(&self, other: &WellKnownSid) -> bool {
+        self.matches(Sid::SID_REVISION, other.authority, other.sub_authorities)
+    }
+}
+
+impl PartialEq<Sid> for WellKnownSid {
+    fn eq(&self, other: &Sid) -> bool {
+        other == self
+    }
+}
+
+pub const NULL: WellKnownSid = WellKnownSid::new("Null", 0, &[0]);
+pub const EVERYONE: WellKnownSid = WellKnownSid::new("Everyone", 1, &[0]);
+pub const LOCAL: WellKnownSid = WellKnownSid::new("Local", 2, &[0]);
+pub const CREATOR_OWNER: WellKnownSid = WellKnownSid::new("Creator Owner", 3, &[0]);
+pub const CREATOR_GROUP: WellKnownSid = WellKnownSid::new("Creator Group", 3, &[1]);
+pub const OWNER_RIGHTS: WellKnownSid = WellKnownSid::new("Owner Rights", 3, &[4]);
+pub const NETWORK: WellKnownSid = WellKnownSid::new("Network", 5, &[2]);
+pub const INTERACTIVE: WellKnownSid = WellKnownSid::new("Interactive", 5, &[4]);
+pub const SERVICE: WellKnownSid = WellKnownSid::new("Service", 5, &[6]);
+pub const ANONYMOUS: WellKnownSid = WellKnownSid::new("Anonymous Logon", 5, &[7]);
+pub const SELF: WellKnownSid = WellKnownSid::new("Principal Self", 5, &[10]);
+pub const AUTHENTICATED_USERS: WellKnownSid = WellKnownSid::new("Authenticated Users", 5, &[11]);
+pub const LOCAL_SYSTEM: WellKnownSid = WellKnownSid::new("Local System", 5, &[18]);
+pub const LOCAL_SERVICE: WellKnownSid = WellKnownSid::new("Local Service", 5, &[19]);
+pub const NETWORK_SERVICE: WellKnownSid = WellKnownSid::new("Network Service", 5, &[20]);
+pub const ADMINISTRATORS: WellKnownSid = WellKnownSid::new("Administrators", 5, &[32, 544]);
+pub const USERS: WellKnownSid = WellKnownSid::new("Users", 5, &[32, 545]);
+pub const GUESTS: WellKnownSid = WellKnownSid::new("Guests", 5, &[32, 546]);
+pub const POWER_USERS: WellKnownSid = WellKnownSid::new("Power Users", 5, &[32, 547]);
+pub const BACKUP_OPERATORS: WellKnownSid = WellKnownSid::new("Backup Operators", 5, &[32, 551]);
+pub const TRUSTED_INSTALLER: WellKnownSid = WellKnownSid::new(
+    "TrustedInstaller",
+    5,
+    &[
+        80,
+        956_008_885,
+        3_418_522_649,
+        1_831_038_044,
+        1_853_292_631,
+        2_271_478_464,
+    ],
+);
+
+pub const ALL: &[WellKnownSid] = &[
+    NULL,
+    EVERYONE,
+    LOCAL,
+    CREATOR_OWNER,
+    CREATOR_GROUP,
+    OWNER_RIGHTS,
+    NETWORK,
+    INTERACTIVE,
+    SERVICE,
+    ANONYMOUS,
+    SELF,
+    AUTHENTICATED_USERS,
+    LOCAL_SYSTEM,
+    LOCAL_SERVICE,
+    NETWORK_SERVICE,
+    ADMINISTRATORS,
+    USERS,
+    GUESTS,
+    POWER_USERS,
+    BACKUP_OPERATORS,
+    TRUSTED_INSTALLER,
+];
+
+/// Finds the well known SID matching `sid`, if any.
+pub fn lookup(sid: &Sid) -> Option<&'static WellKnownSid> {
+    ALL.iter().find(|well_known| *sid == **well_known)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_well_known_sids_display() {
+        let expected = [
+            (EVERYONE, "S-1-1-0"),
+            (CREATOR_OWNER, "S-1-3-0"),
+            (AUTHENTICATED_USERS, "S-1-5-11"),
+            (LOCAL_SYSTEM, "S-1-5-18"),
+            (LOCAL_SERVICE, "S-1-5-19"),
+            (NETWORK_SERVICE, "S-1-5-20"),
+            (ADMINISTRATORS, "S-1-5-32-544"),
+            (USERS, "S-1-5-32-545"),
+            (
+                TRUSTED_INSTALLER,
+                "S-1-5-80-956008885-3418522649-1831038044-1853292631-2271478464",
+            ),
+        ];
+
+        for (well_known, string) in expected.iter() {
+            assert_eq!(well_known.to_string(), *string);
+            assert_eq!(well_known.to_sid().to_string(), *string);
+        }
+
+        for well_known in ALL {
+            assert_eq!(well_known.to_sid(), *well_known);
+        }
+    }
+
+    #[test]
+    fn test_compares_with_parsed_sid() {
+        let sid: Sid = "S-1-5-32-544".parse().unwrap();
+
+        assert!(sid == ADMINISTRATORS);
+        assert!(ADMINISTRATORS == sid);
+        assert!(sid != USERS);
+        assert!(sid != LOCAL_SYSTEM);
+
+        assert_eq!(lookup(&sid), Some(&ADMINISTRATORS));
+        assert_eq!(lookup(&"S-1-5-21-1-2-3-500".parse().unwrap()), None);
+    }
+}
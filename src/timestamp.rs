@@ -1,13 +1,20 @@
 //! Provides utilities for reading various NT timestamp formats.
-use crate::err::Result;
-use byteorder::{LittleEndian, ReadBytesExt}; //Reading little endian data structs
-use chrono::{DateTime, Duration, NaiveDate, Utc};
-
+use crate::err::{Error, Result};
+use byteorder::{ByteOrder, LittleEndian, ReadBytesExt}; //Reading little endian data structs
+use chrono::{
+    DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, SecondsFormat, Timelike, Utc,
+};
+use serde::{de, ser};
+
+use std::convert::TryFrom;
 use std::fmt;
 use std::fmt::{Debug, Display};
 use std::io::{Cursor, Read};
+use std::ops::Sub;
+use std::time::{SystemTime, UNIX_EPOCH};
 
-#[derive(Clone)]
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 /// https://docs.microsoft.com/en-us/windows/desktop/api/minwinbase/ns-minwinbase-filetime
 /// Contains a 64-bit value representing the number of 100-nanosecond intervals since January 1, 1601 (UTC).
 /// # Example
@@ -23,28 +30,156 @@ use std::io::{Cursor, Read};
 /// ```
 pub struct WinTimestamp(u64);
 
+/// The number of 100-nanosecond intervals between 1601-01-01 and 1970-01-01.
+const UNIX_EPOCH_INTERVALS: u64 = 116_444_736_000_000_000;
+
 impl WinTimestamp {
+    pub const MIN: WinTimestamp = WinTimestamp(0);
+    pub const MAX: WinTimestamp = WinTimestamp(u64::MAX);
+
+    /// The current time as a FILETIME.
+    pub fn now() -> WinTimestamp {
+        let intervals = match SystemTime::now().duration_since(UNIX_EPOCH) {
+            Ok(since_epoch) => UNIX_EPOCH_INTERVALS + (since_epoch.as_nanos() / 100) as u64,
+            Err(err) => UNIX_EPOCH_INTERVALS - (err.duration().as_nanos() / 100) as u64,
+        };
+
+        WinTimestamp(intervals)
+    }
+
     pub fn new(buffer: &[u8]) -> Result<Self> {
         Self::from_reader(&mut Cursor::new(buffer))
     }
 
+    /// Encodes a datetime as a FILETIME, truncating anything below 100 nanoseconds.
+    /// Errors for datetimes before 1601, or too far in the future to fit in 64 bits.
+    pub fn from_datetime(datetime: DateTime<Utc>) -> Result<WinTimestamp> {
+        let out_of_range = || Error::DateTimeOutOfRange { datetime };
+
+        let since_windows_epoch = datetime.naive_utc() - windows_epoch();
+
+        if since_windows_epoch < Duration::zero() {
+            return Err(out_of_range());
+        }
+
+        // Both parts are non-negative, since the duration is.
+        let seconds = since_windows_epoch.num_seconds() as u64;
+        let sub_second_intervals = u64::from(since_windows_epoch.subsec_nanos() as u32 / 100);
+
+        let intervals = seconds
+            .checked_mul(10_000_000)
+            .and_then(|intervals| intervals.checked_add(sub_second_intervals))
+            .ok_or_else(out_of_range)?;
+
+        Ok(WinTimestamp(intervals))
+    }
+
+    /// The raw number of 100-nanosecond intervals since 1601-01-01.
+    pub fn as_u64(&self) -> u64 {
+        self.0
+    }
+
     #[inline]
     pub fn from_reader<R: Read>(reader: &mut R) -> Result<WinTimestamp> {
-        let win_timestamp = WinTimestamp(reader.read_u64::<LittleEndian>()?);
+        Self::from_reader_endian::<R, LittleEndian>(reader)
+    }
+
+    /// Reads a timestamp stored with the byte order `B`.
+    #[inline]
+    pub fn from_reader_endian<R: Read, B: ByteOrder>(reader: &mut R) -> Result<WinTimestamp> {
+        let win_timestamp = WinTimestamp(reader.read_u64::<B>()?);
         Ok(win_timestamp)
     }
 
+    /// Formats the timestamp as RFC 3339, e.g. `2013-10-19T12:16:53.276040Z`.
+    /// This is the same format used when serializing.
+    pub fn to_rfc3339(&self) -> String {
+        self.to_datetime()
+            .to_rfc3339_opts(SecondsFormat::AutoSi, true)
+    }
+
+    /// Panics if the timestamp can't be represented as a `DateTime`, see `try_to_datetime`.
     pub fn to_datetime(&self) -> DateTime<Utc> {
+        self.try_to_datetime().expect("to_datetime() should work")
+    }
+
+    pub fn try_to_datetime(&self) -> Result<DateTime<Utc>> {
+        self.try_to_naive()
+            .map(|x| DateTime::from_naive_utc_and_offset(x, Utc))
+    }
+
+    /// Like `to_datetime`, but as a naive datetime in UTC.
+    /// Panics if the timestamp can't be represented, see `try_to_naive`.
+    pub fn to_naive(&self) -> NaiveDateTime {
+        self.try_to_naive().expect("to_naive() should work")
+    }
+
+    pub fn try_to_naive(&self) -> Result<NaiveDateTime> {
         let nanos_since_windows_epoch = self.0;
 
         // Add microseconds to timestamp via Duration
-        DateTime::from_utc(
-            NaiveDate::from_ymd_opt(1601, 1, 1).and_then(|x| x.and_hms_nano_opt(0, 0, 0, 0)).expect("to_datetime() should work") + Duration::microseconds((nanos_since_windows_epoch / 10) as i64),
-            Utc,
-        )
+        windows_epoch()
+            .checked_add_signed(Duration::microseconds(
+                (nanos_since_windows_epoch / 10) as i64,
+            ))
+            .ok_or(Error::TimestampOutOfRange { value: self.0 })
+    }
+
+    /// Interprets the timestamp as local time rather than UTC, as some legacy structures store it.
+    /// Panics if it doesn't exist in the local timezone, see `try_to_local_datetime`.
+    #[cfg(feature = "clock")]
+    pub fn to_local_datetime(&self) -> DateTime<chrono::Local> {
+        self.try_to_local_datetime()
+            .expect("to_local_datetime() should work")
+    }
+
+    /// Errors for times skipped by a daylight saving transition. For times which occur twice,
+    /// the earlier one is used.
+    #[cfg(feature = "clock")]
+    pub fn try_to_local_datetime(&self) -> Result<DateTime<chrono::Local>> {
+        use chrono::TimeZone;
+
+        let datetime = self.try_to_naive()?;
+
+        chrono::Local
+            .from_local_datetime(&datetime)
+            .earliest()
+            .ok_or(Error::NonexistentLocalTime { datetime })
+    }
+
+    /// The signed duration from `other` to `self`, at the full 100-nanosecond precision.
+    /// Also available as `self - other`.
+    pub fn signed_duration_since(&self, other: &WinTimestamp) -> Duration {
+        let intervals = i128::from(self.0) - i128::from(other.0);
+
+        // Any difference between two `u64` interval counts is well within the range of a `Duration`.
+        Duration::seconds((intervals / 10_000_000) as i64)
+            + Duration::nanoseconds((intervals % 10_000_000) as i64 * 100)
     }
 }
 
+impl Sub for WinTimestamp {
+    type Output = Duration;
+
+    fn sub(self, rhs: WinTimestamp) -> Duration {
+        self.signed_duration_since(&rhs)
+    }
+}
+
+impl<'a> Sub<&'a WinTimestamp> for &'a WinTimestamp {
+    type Output = Duration;
+
+    fn sub(self, rhs: &'a WinTimestamp) -> Duration {
+        self.signed_duration_since(rhs)
+    }
+}
+
+fn windows_epoch() -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(1601, 1, 1)
+        .and_then(|x| x.and_hms_nano_opt(0, 0, 0, 0))
+        .expect("1601-01-01 is a valid date")
+}
+
 impl Display for WinTimestamp {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{}", self.to_datetime())
@@ -57,8 +192,60 @@ impl Debug for WinTimestamp {
     }
 }
 
-#[derive(Clone)]
+impl_json_schema_as_string!(WinTimestamp, Some("date-time"));
+
+/// Timestamps are serialized as RFC 3339 strings.
+impl ser::Serialize for WinTimestamp {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        self.to_datetime().serialize(serializer)
+    }
+}
+
+/// Accepts either the raw FILETIME value, or an RFC 3339 string as produced when serializing.
+impl<'de> de::Deserialize<'de> for WinTimestamp {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        struct WinTimestampVisitor;
+
+        impl<'de> de::Visitor<'de> for WinTimestampVisitor {
+            type Value = WinTimestamp;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "a FILETIME integer or an RFC 3339 string")
+            }
+
+            fn visit_u64<E: de::Error>(self, value: u64) -> std::result::Result<WinTimestamp, E> {
+                Ok(WinTimestamp(value))
+            }
+
+            fn visit_i64<E: de::Error>(self, value: i64) -> std::result::Result<WinTimestamp, E> {
+                u64::try_from(value)
+                    .map(WinTimestamp)
+                    .map_err(|_| E::invalid_value(de::Unexpected::Signed(value), &self))
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> std::result::Result<WinTimestamp, E> {
+                let datetime = DateTime::parse_from_rfc3339(value).map_err(E::custom)?;
+
+                WinTimestamp::from_datetime(datetime.with_timezone(&Utc)).map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_any(WinTimestampVisitor)
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 /// MS-DOS date and MS-DOS time are packed 16-bit values that specify the month, day, year, and time of day an MS-DOS file was last written to.
+///
+/// Ordering compares the packed value directly. This matches chronological order only because
+/// the year occupies the high bits, followed by the month and then the day.
 pub struct DosDate(u16);
 
 impl DosDate {
@@ -86,12 +273,17 @@ impl DosDate {
 
         let year = (self.0 >> 9) + 1980;
 
-        chrono::NaiveDate::from_ymd_opt(i32::from(year), u32::from(month), u32::from(day)).expect("to_date() should work")
+        chrono::NaiveDate::from_ymd_opt(i32::from(year), u32::from(month), u32::from(day))
+            .expect("to_date() should work")
     }
 
     pub fn to_date_formatted(&self, format: &str) -> String {
         self.to_date().format(format).to_string()
     }
+
+    pub fn weekday(&self) -> chrono::Weekday {
+        self.to_date().weekday()
+    }
 }
 
 impl Display for DosDate {
@@ -106,7 +298,21 @@ impl Debug for DosDate {
     }
 }
 
+impl From<u16> for DosDate {
+    fn from(date: u16) -> Self {
+        DosDate::new(date)
+    }
+}
+
+/// The raw packed value, the inverse of `From<u16>`.
+impl From<DosDate> for u16 {
+    fn from(date: DosDate) -> Self {
+        date.0
+    }
+}
+
 #[derive(Clone)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 /// MS-DOS date and MS-DOS time are packed 16-bit values that specify the month, day, year, and time of day an MS-DOS file was last written to.
 pub struct DosTime(u16);
 impl DosTime {
@@ -118,12 +324,19 @@ impl DosTime {
         Ok(DosTime::new(buffer.read_u16::<LittleEndian>()?))
     }
 
+    /// Panics if the time is out of range, see `to_time_checked`.
     pub fn to_time(&self) -> chrono::NaiveTime {
+        self.to_time_checked().expect("to_time() should work")
+    }
+
+    /// The bit fields can hold values such as second 62 or hour 31, which are rejected here.
+    pub fn to_time_checked(&self) -> Result<chrono::NaiveTime> {
         let sec = (self.0 & 0x1F) * 2;
         let min = (self.0 >> 5) & 0x3F;
         let hour = (self.0 >> 11) & 0x1F;
 
-        chrono::NaiveTime::from_hms_opt(u32::from(hour), u32::from(min), u32::from(sec)).expect("to_time() should work")
+        chrono::NaiveTime::from_hms_opt(u32::from(hour), u32::from(min), u32::from(sec))
+            .ok_or(Error::InvalidDosTime { raw: self.0 })
     }
 }
 
@@ -139,7 +352,21 @@ impl Debug for DosTime {
     }
 }
 
+impl From<u16> for DosTime {
+    fn from(time: u16) -> Self {
+        DosTime::new(time)
+    }
+}
+
+/// The raw packed value, the inverse of `From<u16>`.
+impl From<DosTime> for u16 {
+    fn from(time: DosTime) -> Self {
+        time.0
+    }
+}
+
 #[derive(Clone)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct DosDateTime {
     date: u16,
     time: u16,
@@ -159,6 +386,50 @@ impl DosDateTime {
     pub fn to_datetime(&self) -> chrono::NaiveDateTime {
         chrono::NaiveDateTime::new(DosDate(self.date).to_date(), DosTime(self.time).to_time())
     }
+
+    /// Packs a date and time, which must be between 1980 and 2107.
+    /// Seconds are rounded down to the 2 second resolution of the format, and fractions are dropped.
+    pub fn from_datetime(datetime: NaiveDateTime) -> Result<DosDateTime> {
+        let year = datetime.year() - 1980;
+
+        if !(0..=0x7F).contains(&year) {
+            return Err(Error::DosDateTimeOutOfRange { datetime });
+        }
+
+        let date = (year as u16) << 9 | (datetime.month() as u16) << 5 | datetime.day() as u16;
+        let time = (datetime.hour() as u16) << 11
+            | (datetime.minute() as u16) << 5
+            | (datetime.second() / 2) as u16;
+
+        Ok(DosDateTime::new(date, time))
+    }
+
+    /// Adds `duration` and re-encodes the result, see `from_datetime`.
+    pub fn checked_add_signed(&self, duration: Duration) -> Result<DosDateTime> {
+        let datetime = self.to_datetime();
+
+        match datetime.checked_add_signed(duration) {
+            Some(datetime) => Self::from_datetime(datetime),
+            None => Err(Error::DosDateTimeOutOfRange { datetime }),
+        }
+    }
+
+    /// Subtracts `duration` and re-encodes the result, see `from_datetime`.
+    pub fn checked_sub_signed(&self, duration: Duration) -> Result<DosDateTime> {
+        let datetime = self.to_datetime();
+
+        match datetime.checked_sub_signed(duration) {
+            Some(datetime) => Self::from_datetime(datetime),
+            None => Err(Error::DosDateTimeOutOfRange { datetime }),
+        }
+    }
+}
+
+/// The date in the low 16 bits and the time in the high 16 bits, the inverse of `From<u32>`.
+impl From<DosDateTime> for u32 {
+    fn from(datetime: DosDateTime) -> Self {
+        u32::from(datetime.time) << 16 | u32::from(datetime.date)
+    }
 }
 
 impl From<u32> for DosDateTime {
@@ -183,8 +454,12 @@ impl Debug for DosDateTime {
 
 #[cfg(test)]
 mod tests {
+    use crate::err::Error;
     use crate::timestamp::{DosDate, DosDateTime, DosTime, WinTimestamp};
+    use byteorder::{BigEndian, LittleEndian};
+    use chrono::{DateTime, Duration, NaiveDate, Utc};
     use std::io::Cursor;
+    use std::time::SystemTime;
 
     #[test]
     fn test_win_timestamp() {
@@ -196,6 +471,173 @@ mod tests {
         assert_eq!(format!("{:?}", timestamp), "2013-10-19 12:16:53.276040 UTC");
     }
 
+    #[test]
+    fn test_win_timestamp_from_reader_endian() {
+        let little_endian: &[u8] = &[0x53, 0xC7, 0x8B, 0x18, 0xC5, 0xCC, 0xCE, 0x01];
+        let big_endian: &[u8] = &[0x01, 0xCE, 0xCC, 0xC5, 0x18, 0x8B, 0xC7, 0x53];
+
+        let from_little =
+            WinTimestamp::from_reader_endian::<_, LittleEndian>(&mut Cursor::new(little_endian))
+                .unwrap();
+        let from_big =
+            WinTimestamp::from_reader_endian::<_, BigEndian>(&mut Cursor::new(big_endian)).unwrap();
+
+        assert_eq!(format!("{}", from_little), "2013-10-19 12:16:53.276040 UTC");
+        assert_eq!(format!("{}", from_big), "2013-10-19 12:16:53.276040 UTC");
+    }
+
+    #[test]
+    fn test_win_timestamp_bounds() {
+        let now = WinTimestamp::now().to_datetime();
+        let expected = DateTime::<Utc>::from(SystemTime::now());
+        assert!((expected - now).num_milliseconds().abs() < 1000);
+
+        assert_eq!(
+            WinTimestamp::MIN.to_datetime().to_string(),
+            "1601-01-01 00:00:00 UTC"
+        );
+        // Even the largest value fits in a `DateTime` at microsecond precision.
+        assert_eq!(
+            WinTimestamp::MAX.try_to_datetime().unwrap().to_string(),
+            "+60056-05-28 05:36:10.955161 UTC"
+        );
+
+        assert!(WinTimestamp::MIN < WinTimestamp::now());
+        assert!(WinTimestamp::now() < WinTimestamp::MAX);
+    }
+
+    #[test]
+    fn test_win_timestamp_to_naive() {
+        let raw_timestamp: &[u8] = &[0x53, 0xC7, 0x8B, 0x18, 0xC5, 0xCC, 0xCE, 0x01];
+        let timestamp = WinTimestamp::new(raw_timestamp).unwrap();
+
+        assert_eq!(timestamp.to_naive(), timestamp.to_datetime().naive_utc());
+        assert_eq!(
+            timestamp.to_naive().to_string(),
+            "2013-10-19 12:16:53.276040"
+        );
+        assert_eq!(
+            WinTimestamp::MAX.try_to_naive().unwrap(),
+            WinTimestamp::MAX.try_to_datetime().unwrap().naive_utc()
+        );
+    }
+
+    #[cfg(feature = "clock")]
+    #[test]
+    fn test_win_timestamp_to_local_datetime() {
+        let raw_timestamp: &[u8] = &[0x53, 0xC7, 0x8B, 0x18, 0xC5, 0xCC, 0xCE, 0x01];
+        let timestamp = WinTimestamp::new(raw_timestamp).unwrap();
+
+        // Whatever the timezone, the wall clock time is the stored one.
+        let local = timestamp.to_local_datetime();
+        assert_eq!(local.naive_local(), timestamp.to_naive());
+        assert_eq!(
+            local.naive_utc(),
+            timestamp.to_naive() - Duration::seconds(i64::from(local.offset().local_minus_utc()))
+        );
+    }
+
+    #[test]
+    fn test_win_timestamp_sub() {
+        let raw_timestamp: &[u8] = &[0x53, 0xC7, 0x8B, 0x18, 0xC5, 0xCC, 0xCE, 0x01];
+        let earlier = WinTimestamp::new(raw_timestamp).unwrap();
+
+        // 90 seconds and 1.2 microseconds later.
+        let later = WinTimestamp(earlier.as_u64() + 900_000_012);
+
+        let expected = Duration::seconds(90) + Duration::nanoseconds(1_200);
+        assert_eq!(&later - &earlier, expected);
+        assert_eq!(&earlier - &later, -expected);
+        assert_eq!(later.clone() - later, Duration::zero());
+
+        assert_eq!(
+            WinTimestamp::MAX - WinTimestamp::MIN,
+            Duration::seconds(1_844_674_407_370) + Duration::nanoseconds(955_161_500)
+        );
+    }
+
+    #[test]
+    fn test_win_timestamp_from_datetime() {
+        let raw_timestamp: &[u8] = &[0x53, 0xC7, 0x8B, 0x18, 0xC5, 0xCC, 0xCE, 0x01];
+        let timestamp = WinTimestamp::new(raw_timestamp).unwrap();
+
+        let round_tripped = WinTimestamp::from_datetime(timestamp.to_datetime()).unwrap();
+
+        // `to_datetime` only has microsecond precision.
+        assert_eq!(
+            round_tripped.as_u64(),
+            timestamp.as_u64() - timestamp.as_u64() % 10
+        );
+
+        let before_1601 = WinTimestamp::MIN.to_datetime() - chrono::Duration::seconds(1);
+        assert!(WinTimestamp::from_datetime(before_1601).is_err());
+    }
+
+    #[test]
+    fn test_win_timestamp_serialize() {
+        let raw_timestamp: &[u8] = &[0x53, 0xC7, 0x8B, 0x18, 0xC5, 0xCC, 0xCE, 0x01];
+
+        let timestamp = WinTimestamp::new(raw_timestamp).unwrap();
+
+        assert_eq!(
+            serde_json::to_string(&timestamp).unwrap(),
+            "\"2013-10-19T12:16:53.276040Z\""
+        );
+        assert_eq!(timestamp.to_rfc3339(), "2013-10-19T12:16:53.276040Z");
+        assert_eq!(format!("{}", timestamp), "2013-10-19 12:16:53.276040 UTC");
+    }
+
+    #[test]
+    fn test_dosdate_weekday_and_ordering() {
+        // 2012-03-12 was a Monday.
+        assert_eq!(DosDate(16492).weekday(), chrono::Weekday::Mon);
+        // 2012-03-12, 2012-03-13, 2012-01-01, 2011-12-31
+        // 2011-12-31, 2012-01-01, 2012-03-12, 2012-03-13
+        let mut dates = [
+            DosDate(16492),
+            DosDate(16493),
+            DosDate(16417),
+            DosDate(16287),
+        ];
+        dates.sort();
+
+        let sorted: Vec<String> = dates.iter().map(|date| date.to_string()).collect();
+        assert_eq!(
+            sorted,
+            vec!["2011-12-31", "2012-01-01", "2012-03-12", "2012-03-13"]
+        );
+    }
+
+    #[test]
+    fn test_dostime_checked() {
+        assert_eq!(
+            DosTime(43874).to_time_checked().unwrap().to_string(),
+            "21:27:04"
+        );
+
+        // Second 62
+        assert!(DosTime(0x001F).to_time_checked().is_err());
+        // Hour 30
+        assert!(DosTime(30 << 11).to_time_checked().is_err());
+    }
+
+    #[test]
+    fn test_win_timestamp_deserialize() {
+        let raw_timestamp: &[u8] = &[0x50, 0xC7, 0x8B, 0x18, 0xC5, 0xCC, 0xCE, 0x01];
+        let timestamp = WinTimestamp::new(raw_timestamp).unwrap();
+
+        let from_integer: WinTimestamp =
+            serde_json::from_str(&timestamp.as_u64().to_string()).unwrap();
+        let from_string: WinTimestamp =
+            serde_json::from_str("\"2013-10-19T12:16:53.276040Z\"").unwrap();
+
+        assert_eq!(from_integer, timestamp);
+        assert_eq!(from_string, timestamp);
+
+        assert!(serde_json::from_str::<WinTimestamp>("-1").is_err());
+        assert!(serde_json::from_str::<WinTimestamp>("\"yesterday\"").is_err());
+    }
+
     #[test]
     fn test_dosdate() {
         let dos_date = DosDate(16492);
@@ -203,6 +645,19 @@ mod tests {
         assert_eq!(format!("{:?}", dos_date), "2012-03-12");
     }
 
+    #[test]
+    fn test_dos_date_and_time_u16_conversions() {
+        let date = DosDate::from(16492);
+        assert_eq!(u16::from(date.clone()), 16492);
+        assert_eq!(DosDate::from(u16::from(date.clone())), date);
+
+        let time: DosTime = 43874.into();
+        assert_eq!(time.to_string(), "21:27:04");
+
+        let raw: u16 = time.into();
+        assert_eq!(raw, 43874);
+    }
+
     #[test]
     fn test_dosdate_zeros() {
         let raw_date: &[u8] = &[0x00, 0x00];
@@ -232,4 +687,43 @@ mod tests {
 
         assert_eq!(format!("{:?}", dos_time), "2012-03-12 21:27:04");
     }
+
+    #[test]
+    fn test_dos_datetime_from_datetime() {
+        let decoded = DosDateTime::from(2_875_342_956).to_datetime();
+
+        let encoded = DosDateTime::from_datetime(decoded).unwrap();
+        assert_eq!(u32::from(encoded), 2_875_342_956);
+
+        // Odd seconds are rounded down.
+        let odd = decoded + Duration::seconds(1);
+        assert_eq!(
+            u32::from(DosDateTime::from_datetime(odd).unwrap()),
+            2_875_342_956
+        );
+
+        let later = DosDateTime::from(2_875_342_956)
+            .checked_add_signed(Duration::seconds(2))
+            .unwrap();
+        assert_eq!(format!("{}", later), "2012-03-12 21:27:06");
+        assert_eq!(
+            u32::from(later.checked_sub_signed(Duration::seconds(2)).unwrap()),
+            2_875_342_956
+        );
+
+        let too_early = NaiveDate::from_ymd_opt(1979, 12, 31)
+            .unwrap()
+            .and_hms_opt(23, 59, 59)
+            .unwrap();
+        assert!(matches!(
+            DosDateTime::from_datetime(too_early),
+            Err(Error::DosDateTimeOutOfRange { .. })
+        ));
+
+        let too_late = NaiveDate::from_ymd_opt(2108, 1, 1)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+        assert!(DosDateTime::from_datetime(too_late).is_err());
+    }
 }
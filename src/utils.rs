@@ -1,4 +1,309 @@
+use crate::err::{Error, Result};
+use byteorder::{LittleEndian, ReadBytesExt};
+
+use std::io::{self, Read};
+
 pub fn to_hex_string(bytes: &[u8]) -> String {
-    let strings: Vec<String> = bytes.iter().map(|b| format!("{:02X}", b)).collect();
-    strings.join("")
+    const DIGITS: &[u8; 16] = b"0123456789ABCDEF";
+
+    let mut s = String::with_capacity(bytes.len() * 2);
+
+    for b in bytes {
+        s.push(char::from(DIGITS[usize::from(b >> 4)]));
+        s.push(char::from(DIGITS[usize::from(b & 0x0F)]));
+    }
+
+    s
+}
+
+/// Parses a string of hex digit pairs (as emitted by `to_hex_string`) back into bytes.
+pub fn from_hex_string(s: &str) -> Result<Vec<u8>> {
+    let invalid = || Error::InvalidHexString {
+        value: s.to_string(),
+    };
+
+    if !s.len().is_multiple_of(2) {
+        return Err(invalid());
+    }
+
+    s.as_bytes()
+        .chunks_exact(2)
+        .map(|pair| match (hex_digit(pair[0]), hex_digit(pair[1])) {
+            (Some(high), Some(low)) => Ok(high << 4 | low),
+            _ => Err(invalid()),
+        })
+        .collect()
+}
+
+fn hex_digit(c: u8) -> Option<u8> {
+    match c {
+        b'0'..=b'9' => Some(c - b'0'),
+        b'a'..=b'f' => Some(c - b'a' + 10),
+        b'A'..=b'F' => Some(c - b'A' + 10),
+        _ => None,
+    }
+}
+
+/// Reads `code_units` UTF-16LE code units from the reader and decodes them into a `String`.
+///
+/// When `lossy` is set, unpaired surrogates are replaced with `U+FFFD`,
+/// otherwise they result in an error.
+pub fn read_utf16_le<R: Read>(reader: &mut R, code_units: usize, lossy: bool) -> Result<String> {
+    let mut buffer = vec![0; code_units];
+    reader.read_u16_into::<LittleEndian>(&mut buffer)?;
+
+    decode_utf16(&buffer, lossy)
+}
+
+/// Reads UTF-16LE code units from the reader until a null code unit is found,
+/// and decodes them into a `String` (the terminator is consumed, but not included).
+///
+/// When `lossy` is set, unpaired surrogates are replaced with `U+FFFD`,
+/// otherwise they result in an error.
+pub fn read_utf16_le_null_terminated<R: Read>(reader: &mut R, lossy: bool) -> Result<String> {
+    let mut buffer = Vec::new();
+
+    loop {
+        let code_unit = reader.read_u16::<LittleEndian>()?;
+
+        if code_unit == 0 {
+            break;
+        }
+
+        buffer.push(code_unit);
+    }
+
+    decode_utf16(&buffer, lossy)
+}
+
+/// Fills as much of `buffer` as the reader allows, returning the number of bytes read.
+/// Unlike `read_exact`, hitting EOF is not an error, so callers can report how short the data was.
+pub(crate) fn read_up_to<R: Read>(reader: &mut R, buffer: &mut [u8]) -> io::Result<usize> {
+    let mut read = 0;
+
+    while read < buffer.len() {
+        match reader.read(&mut buffer[read..]) {
+            Ok(0) => break,
+            Ok(n) => read += n,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err),
+        }
+    }
+
+    Ok(read)
+}
+
+/// Counts the bytes read through it, so the size of a structure parsed from a non-seekable
+/// reader is known afterwards. When created with `with_crc32`, it also computes the CRC32 (IEEE)
+/// of those bytes.
+///
+/// ```
+/// # use winstructs::CountingReader;
+/// # use winstructs::security::Sid;
+/// # use std::io::Cursor;
+/// let raw_sid: &[u8] = &[0x01, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x05, 0x12, 0x00, 0x00, 0x00, 0xFF];
+///
+/// let mut reader = CountingReader::new(Cursor::new(raw_sid));
+/// Sid::from_reader(&mut reader).unwrap();
+///
+/// assert_eq!(reader.bytes_read(), 12);
+/// ```
+#[derive(Debug)]
+pub struct CountingReader<R> {
+    inner: R,
+    bytes_read: u64,
+    /// The running CRC, before the final inversion.
+    crc_state: Option<u32>,
+}
+
+/// A `CountingReader` created with `with_crc32`, for checking a parsed structure against
+/// the length and checksum stored by its container.
+///
+/// ```
+/// # use winstructs::VerifyingReader;
+/// # use winstructs::security::Sid;
+/// # use std::io::Cursor;
+/// let raw_sid: &[u8] = &[0x01, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x05, 0x12, 0x00, 0x00, 0x00];
+///
+/// let mut reader = VerifyingReader::with_crc32(Cursor::new(raw_sid));
+/// let sid = Sid::from_reader(&mut reader).unwrap();
+///
+/// assert_eq!(reader.bytes_read(), sid.byte_len() as u64);
+/// assert_eq!(reader.crc32(), Some(0x130E_45A4));
+/// ```
+pub type VerifyingReader<R> = CountingReader<R>;
+
+impl<R> CountingReader<R> {
+    /// Only tracks the number of bytes read.
+    pub fn new(inner: R) -> Self {
+        CountingReader {
+            inner,
+            bytes_read: 0,
+            crc_state: None,
+        }
+    }
+
+    /// Tracks the number of bytes read and their CRC32.
+    pub fn with_crc32(inner: R) -> Self {
+        CountingReader {
+            crc_state: Some(!0),
+            ..Self::new(inner)
+        }
+    }
+
+    pub fn bytes_read(&self) -> u64 {
+        self.bytes_read
+    }
+
+    /// The CRC32 of the bytes read so far, if the reader was created with `with_crc32`.
+    pub fn crc32(&self) -> Option<u32> {
+        self.crc_state.map(|state| !state)
+    }
+
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.bytes_read += n as u64;
+
+        if let Some(state) = &mut self.crc_state {
+            for byte in &buf[..n] {
+                *state = CRC32_TABLE[usize::from(*state as u8 ^ byte)] ^ (*state >> 8);
+            }
+        }
+
+        Ok(n)
+    }
+}
+
+const CRC32_TABLE: [u32; 256] = crc32_table();
+
+/// The lookup table of the reflected CRC32 polynomial used by zip, PNG and Ethernet.
+const fn crc32_table() -> [u32; 256] {
+    let mut table = [0; 256];
+    let mut i = 0;
+
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+
+        table[i] = crc;
+        i += 1;
+    }
+
+    table
+}
+
+fn decode_utf16(code_units: &[u16], lossy: bool) -> Result<String> {
+    if lossy {
+        Ok(String::from_utf16_lossy(code_units))
+    } else {
+        Ok(String::from_utf16(code_units)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{read_utf16_le, read_utf16_le_null_terminated, CountingReader, VerifyingReader};
+    use crate::err::Error;
+    use crate::guid::Guid;
+    use crate::security::{Acl, Sid};
+    use std::io::Cursor;
+
+    #[test]
+    fn test_counting_reader() {
+        let buffer: &[u8] = &[
+            0x01, 0x05, 0x00, 0x00, 0x00, 0x00, 0x00, 0x05, 0x15, 0x00, 0x00, 0x00, 0x01, 0x00,
+            0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00, 0xE9, 0x03, 0x00, 0x00,
+            // A GUID and an empty ACL follow.
+            0x25, 0x96, 0x84, 0x54, 0x78, 0x54, 0x94, 0x49, 0xa5, 0xba, 0x3e, 0x3b, 0x03, 0x28,
+            0xc3, 0x0d, 0x02, 0x00, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00,
+        ];
+
+        let mut reader = CountingReader::new(Cursor::new(buffer));
+
+        let sid = Sid::from_reader(&mut reader).unwrap();
+        assert_eq!(reader.bytes_read(), sid.byte_len() as u64);
+
+        Guid::from_reader(&mut reader).unwrap();
+        assert_eq!(reader.bytes_read(), sid.byte_len() as u64 + 16);
+
+        let acl = Acl::from_reader(&mut reader).unwrap();
+        assert_eq!(reader.bytes_read(), buffer.len() as u64);
+        assert_eq!(acl.byte_len(), 8);
+    }
+
+    #[test]
+    fn test_verifying_reader() {
+        let mut reader = VerifyingReader::with_crc32(Cursor::new(b"123456789"));
+        std::io::copy(&mut reader, &mut std::io::sink()).unwrap();
+
+        assert_eq!(reader.bytes_read(), 9);
+        assert_eq!(reader.crc32(), Some(0xCBF4_3926));
+
+        let buffer: &[u8] = &[
+            0x02, 0x00, 0x1C, 0x00, 0x01, 0x00, 0x00, 0x00, // header
+            0x00, 0x00, 0x14, 0x00, 0xFF, 0x01, 0x1F, 0x00, 0x01, 0x01, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x01, 0x00, 0x00, 0x00, 0x00, // S-1-1-0
+            0xAA, 0xBB, // the next structure
+        ];
+
+        let mut reader = VerifyingReader::with_crc32(Cursor::new(buffer));
+        let acl = Acl::from_reader(&mut reader).unwrap();
+
+        assert_eq!(reader.bytes_read(), acl.byte_len() as u64);
+        assert_eq!(reader.crc32(), Some(0x113D_534A));
+
+        // Without `with_crc32`, only the length is tracked.
+        let mut reader = CountingReader::new(Cursor::new(buffer));
+        Acl::from_reader(&mut reader).unwrap();
+        assert_eq!(reader.crc32(), None);
+    }
+
+    #[test]
+    fn test_read_utf16_le() {
+        // "Hi😀" - the emoji is encoded as a surrogate pair.
+        let buffer: &[u8] = &[0x48, 0x00, 0x69, 0x00, 0x3D, 0xD8, 0x00, 0xDE];
+
+        let s = read_utf16_le(&mut Cursor::new(buffer), 4, false).unwrap();
+        assert_eq!(s, "Hi😀");
+    }
+
+    #[test]
+    fn test_read_utf16_le_null_terminated() {
+        let buffer: &[u8] = &[0x48, 0x00, 0x69, 0x00, 0x00, 0x00, 0x41, 0x00];
+        let mut cursor = Cursor::new(buffer);
+
+        let s = read_utf16_le_null_terminated(&mut cursor, false).unwrap();
+        assert_eq!(s, "Hi");
+        assert_eq!(cursor.position(), 6);
+    }
+
+    #[test]
+    fn test_read_utf16_le_unpaired_surrogate() {
+        let buffer: &[u8] = &[0x48, 0x00, 0x3D, 0xD8, 0x69, 0x00];
+
+        let err = read_utf16_le(&mut Cursor::new(buffer), 3, false).unwrap_err();
+        assert!(matches!(err, Error::Utf16DecodeError { .. }));
+
+        let s = read_utf16_le(&mut Cursor::new(buffer), 3, true).unwrap();
+        assert_eq!(s, "H\u{FFFD}i");
+    }
 }